@@ -0,0 +1,340 @@
+//! Non-interactive subcommands, for calling the solver from scripts and other tools.
+//!
+//! - `console next --history "0012:1,0 3344:0,1"` prints the next guess
+//! - `console candidates --history "..." [--limit <n>]` lists the remaining secrets
+//! - `console solve --secret 3451` prints the solver's line of play for a secret
+//! - `console eval [--step <n>]` plays every n-th secret and prints statistics
+//!
+//! Histories are written like in [solver::state], space separated. `solve` and `eval` always play
+//! from the start, so they reject `--history`. Every subcommand also takes
+//! `--strategy <id>` (See [solver::Strategy::id]) and `--json` for JSON output instead of text.
+//! The exit code is 0 on success, [EXIT_NO_CANDIDATES] if no secret matches the history and
+//! [EXIT_USAGE] for invalid arguments.
+use std::fmt::Write as _;
+
+use serde_json::json;
+use solver::{
+    state::{format_answer, GuessResult},
+    PossibleAnswer, SolverContext, Strategy, MAX_GUESSES, POSSIBLE_ANSWERS,
+};
+
+use crate::symbols::*;
+
+pub const EXIT_NO_CANDIDATES: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+pub const COMMANDS: [&str; 4] = ["next", "candidates", "solve", "eval"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    Usage(String),
+    NoCandidates,
+}
+
+impl CommandError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CommandError::Usage(_) => EXIT_USAGE,
+            CommandError::NoCandidates => EXIT_NO_CANDIDATES,
+        }
+    }
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CommandError::Usage(e) => write!(f, "{}", e),
+            CommandError::NoCandidates => write!(f, "No secret is consistent with the history"),
+        }
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(e: String) -> Self {
+        CommandError::Usage(e)
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    history: Vec<GuessResult>,
+    secret: Option<PossibleAnswer>,
+    strategy: Strategy,
+    limit: Option<usize>,
+    step: Option<usize>,
+    json: bool,
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{} expects a number, got '{}'", flag, value))
+}
+
+/// Parse a history like `0012:1,0 3344:0,1`
+pub fn parse_history(text: &str) -> Result<Vec<GuessResult>, String> {
+    text.split_whitespace()
+        .map(|entry| {
+            let parsed = GuessResult::parse(entry).ok_or_else(|| {
+                format!("Invalid history entry '{}', expected like 0012:1,0", entry)
+            })?;
+            crate::repl::check_result(parsed.correct_positions, parsed.correct_symbols)
+                .map_err(|e| format!("Invalid history entry '{}': {}", entry, e))?;
+            Ok(parsed)
+        })
+        .collect()
+}
+
+fn parse_options(args: &[String]) -> Result<Options, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        if flag == "--json" {
+            options.json = true;
+            continue;
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} expects a value", flag))?;
+        match flag.as_str() {
+            "--history" => options.history = parse_history(value)?,
+            "--secret" => options.secret = Some(parse_code(value)?),
+            "--strategy" => {
+                options.strategy = Strategy::from_id(value)
+                    .ok_or_else(|| format!("Unknown strategy '{}'", value))?
+            }
+            "--limit" => options.limit = Some(parse_number(flag, value)?),
+            "--step" => options.step = Some(parse_number(flag, value)?.max(1)),
+            _ => return Err(format!("Unknown option '{}'", flag)),
+        }
+    }
+    Ok(options)
+}
+
+fn context(options: &Options) -> Result<SolverContext, CommandError> {
+    let mut ctx = SolverContext::with_strategy(options.strategy);
+    for entry in options.history.iter() {
+        ctx.apply_partial_result(entry.guess, entry.correct_positions, entry.correct_symbols);
+    }
+    if ctx.remaining_count() == 0 {
+        return Err(CommandError::NoCandidates);
+    }
+    Ok(ctx)
+}
+
+pub fn code_json(code: PossibleAnswer) -> serde_json::Value {
+    json!({
+        "code": format_answer(code),
+        "symbols": code,
+        "names": code.map(|symbol| &crate::locale::get().names[symbol as usize]),
+    })
+}
+
+/// The next guess of `ctx` and some context about it
+pub fn guess_json(ctx: &SolverContext, guess: PossibleAnswer) -> serde_json::Value {
+    json!({
+        "guess": code_json(guess),
+        "candidates": ctx.remaining_count(),
+        "attempts_left": ctx.attempts_left(),
+        "goes_for_the_win": ctx.goes_for_the_win(),
+    })
+}
+
+/// Run `command` with its arguments, returning what to print
+pub fn run(command: &str, args: &[String]) -> Result<String, CommandError> {
+    let options = parse_options(args)?;
+    if matches!(command, "solve" | "eval") && !options.history.is_empty() {
+        return Err(CommandError::Usage(format!(
+            "{} plays from the start, it doesn't take --history",
+            command
+        )));
+    }
+    let mut out = String::new();
+    match command {
+        "next" => {
+            let mut ctx = context(&options)?;
+            let guess = ctx.guess();
+            if options.json {
+                out = guess_json(&ctx, guess).to_string();
+            } else {
+                let _ = write!(out, "{}\t{}", format_answer(guess), format_guess(guess));
+            }
+        }
+        "candidates" => {
+            let ctx = context(&options)?;
+            let ranked = ctx.rank_candidates();
+            let limit = options.limit.unwrap_or(usize::MAX);
+            if options.json {
+                out = json!({
+                    "count": ranked.len(),
+                    "candidates": ranked.iter().take(limit).map(|candidate| json!({
+                        "secret": code_json(candidate.secret),
+                        "probability": candidate.probability,
+                    })).collect::<Vec<_>>(),
+                })
+                .to_string();
+            } else {
+                for candidate in ranked.iter().take(limit) {
+                    let _ = writeln!(
+                        out,
+                        "{}\t{}\t{}",
+                        format_answer(candidate.secret),
+                        format_guess(candidate.secret),
+                        candidate.probability
+                    );
+                }
+                out.pop();
+            }
+        }
+        "solve" => {
+            let secret = options
+                .secret
+                .ok_or_else(|| "solve needs --secret <code>".to_string())?;
+            let replay = crate::practice::solver_replay(options.strategy, secret);
+            if options.json {
+                out = json!({
+                    "secret": code_json(secret),
+                    "guesses": replay.iter().map(|&(guess, p, s)| json!({
+                        "guess": code_json(guess),
+                        "result": [p, s],
+                    })).collect::<Vec<_>>(),
+                })
+                .to_string();
+            } else {
+                for (guess, p, s) in replay.iter() {
+                    let _ = writeln!(
+                        out,
+                        "{}\t{}\t{},{}",
+                        format_answer(*guess),
+                        format_guess(*guess),
+                        p,
+                        s
+                    );
+                }
+                out.pop();
+            }
+        }
+        "eval" => {
+            let step = options.step.unwrap_or(64);
+            let mut ctx = SolverContext::with_strategy(options.strategy);
+            // How many secrets took each number of guesses
+            let mut histogram = Vec::new();
+            for idx in (0..POSSIBLE_ANSWERS).step_by(step) {
+                let guesses = ctx.solve(solver::idx_to_answer(idx));
+                if histogram.len() <= guesses {
+                    histogram.resize(guesses + 1, 0usize);
+                }
+                histogram[guesses] += 1;
+            }
+            let secrets = histogram.iter().sum::<usize>();
+            let total = histogram
+                .iter()
+                .enumerate()
+                .map(|(guesses, &count)| guesses * count)
+                .sum::<usize>();
+            let average = total as f64 / secrets as f64;
+            let max = histogram.len() - 1;
+            let over_budget = histogram.iter().skip(MAX_GUESSES + 1).sum::<usize>();
+            if options.json {
+                out = json!({
+                    "strategy": options.strategy.id(),
+                    "secrets": secrets,
+                    "average": average,
+                    "max": max,
+                    "over_budget": over_budget,
+                    "histogram": histogram,
+                })
+                .to_string();
+            } else {
+                let _ = writeln!(out, "strategy\t{}", options.strategy.id());
+                let _ = writeln!(out, "secrets\t{}", secrets);
+                let _ = writeln!(out, "average\t{:.4}", average);
+                let _ = writeln!(out, "max\t{}", max);
+                let _ = writeln!(out, "over_budget\t{}", over_budget);
+                for (guesses, count) in histogram.iter().enumerate().filter(|(_, &n)| n > 0) {
+                    let _ = writeln!(out, "guesses_{}\t{}", guesses, count);
+                }
+                out.pop();
+            }
+        }
+        _ => {
+            return Err(CommandError::Usage(format!(
+                "Unknown command '{}'",
+                command
+            )))
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(text: &str) -> Vec<String> {
+        // Split on spaces, except inside quotes
+        text.split('"')
+            .enumerate()
+            .flat_map(|(i, part)| {
+                if i % 2 == 1 {
+                    vec![part.to_string()]
+                } else {
+                    part.split_whitespace().map(str::to_string).collect()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn next_and_candidates() {
+        assert_eq!(run("next", &[]).unwrap(), "0012\tJ J B C");
+        let history = r#"--history "0012:1,0 3344:0,1 5566:0,0""#;
+        let next = run("next", &args(&format!("{} --json", history))).unwrap();
+        let next = serde_json::from_str::<serde_json::Value>(&next).unwrap();
+        let candidates = next["candidates"].as_u64().unwrap() as usize;
+        assert!(candidates > 0);
+
+        let listed = run("candidates", &args(history)).unwrap();
+        assert_eq!(listed.lines().count(), candidates);
+        let limited = run(
+            "candidates",
+            &args(&format!("{} --limit 2 --json", history)),
+        )
+        .unwrap();
+        let limited = serde_json::from_str::<serde_json::Value>(&limited).unwrap();
+        assert_eq!(limited["count"].as_u64().unwrap() as usize, candidates);
+        assert_eq!(limited["candidates"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn solve_and_errors() {
+        let solved = run("solve", &args("--secret 3451")).unwrap();
+        assert!(solved.ends_with("3451\tF >< M B\t4,0"));
+
+        let e = run("next", &args(r#"--history "0012:4,0 0012:0,0""#)).unwrap_err();
+        assert_eq!(e.exit_code(), EXIT_NO_CANDIDATES);
+        for bad in [
+            "--history 0012:3,1",
+            "--history 00x2:1,0",
+            "--strategy nope",
+            "--limit",
+        ] {
+            assert_eq!(run("next", &args(bad)).unwrap_err().exit_code(), EXIT_USAGE);
+        }
+        assert_eq!(run("solve", &[]).unwrap_err().exit_code(), EXIT_USAGE);
+        let with_history = args("--secret 3451 --history 0012:0,1");
+        assert_eq!(
+            run("solve", &with_history).unwrap_err().exit_code(),
+            EXIT_USAGE
+        );
+    }
+
+    #[test]
+    fn eval() {
+        let stats = run("eval", &args("--step 2048 --json")).unwrap();
+        let stats = serde_json::from_str::<serde_json::Value>(&stats).unwrap();
+        assert_eq!(stats["secrets"], 11);
+        assert_eq!(stats["over_budget"], 0);
+        assert_eq!(stats["strategy"], "minimax");
+    }
+}
@@ -0,0 +1,231 @@
+//! The vault symbols drawn as terminal art, from the same `symbolN_smol.bmp` templates the main
+//! program looks for on screen.
+//!
+//! Most templates are the whole diamond shaped tile, so the bright background in its corners is
+//! removed and the art is cropped to the symbol. Each character is a half block covering 2
+//! pixels stacked vertically, which makes a symbol about 8x8 characters.
+use solver::{PossibleAnswer, SYMBOL_COUNT};
+
+const SYMBOL_BMPS: [&[u8]; SYMBOL_COUNT] = [
+    include_bytes!("../../src/bmps/symbol0_smol.bmp"),
+    include_bytes!("../../src/bmps/symbol1_smol.bmp"),
+    include_bytes!("../../src/bmps/symbol2_smol.bmp"),
+    include_bytes!("../../src/bmps/symbol3_smol.bmp"),
+    include_bytes!("../../src/bmps/symbol4_smol.bmp"),
+    include_bytes!("../../src/bmps/symbol5_smol.bmp"),
+    include_bytes!("../../src/bmps/symbol6_smol.bmp"),
+    include_bytes!("../../src/bmps/symbol7_smol.bmp"),
+    include_bytes!("../../src/bmps/symbol8_smol.bmp"),
+    include_bytes!("../../src/bmps/symbol9_smol.bmp"),
+    include_bytes!("../../src/bmps/symbol10_smol.bmp"),
+    include_bytes!("../../src/bmps/symbol11_smol.bmp"),
+];
+
+/// Columns between the symbols of a guess
+const GAP: usize = 2;
+
+/// A black and white image. Pixels are row by row from the top, true for the bright ones
+pub struct Bitmap {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<bool>,
+}
+
+impl Bitmap {
+    fn get(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixels[y * self.width + x]
+    }
+    /// Clear the bright pixels connected to the corners, if all 4 corners are bright (The
+    /// background around a tile)
+    pub fn without_background(mut self) -> Self {
+        let (w, h) = (self.width, self.height);
+        let corners = [(0, 0), (w - 1, 0), (0, h - 1), (w - 1, h - 1)];
+        if !corners.iter().all(|&(x, y)| self.get(x, y)) {
+            return self;
+        }
+        let mut stack = corners.to_vec();
+        while let Some((x, y)) = stack.pop() {
+            if !self.get(x, y) {
+                continue;
+            }
+            self.pixels[y * w + x] = false;
+            stack.extend([
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ]);
+        }
+        self
+    }
+    /// The smallest part of the image with all the bright pixels
+    pub fn cropped(&self) -> Self {
+        let lit = || (0..self.height).flat_map(|y| (0..self.width).map(move |x| (x, y)));
+        let lit = || lit().filter(|&(x, y)| self.get(x, y));
+        let (Some(left), Some(right)) = (lit().map(|p| p.0).min(), lit().map(|p| p.0).max()) else {
+            return Bitmap {
+                width: 0,
+                height: 0,
+                pixels: Vec::new(),
+            };
+        };
+        let top = lit().map(|p| p.1).min().unwrap();
+        let bottom = lit().map(|p| p.1).max().unwrap();
+        let (width, height) = (right - left + 1, bottom - top + 1);
+        Bitmap {
+            width,
+            height,
+            pixels: (top..=bottom)
+                .flat_map(|y| (left..=right).map(move |x| self.get(x, y)))
+                .collect(),
+        }
+    }
+}
+
+/// Parse an uncompressed 24 bit bmp, like the templates
+pub fn parse_bmp(bytes: &[u8]) -> Result<Bitmap, &'static str> {
+    let u32_at = |at: usize| {
+        bytes
+            .get(at..at + 4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .ok_or("Truncated bmp header")
+    };
+    if bytes.get(..2) != Some(b"BM") {
+        return Err("Not a bmp");
+    }
+    let data_start = u32_at(0x0A)? as usize;
+    let width = u32_at(0x12)? as i32;
+    let height = u32_at(0x16)? as i32;
+    let bits_per_pixel = u32_at(0x1C)? & 0xFFFF;
+    let compression = u32_at(0x1E)?;
+    if bits_per_pixel != 24 || compression != 0 {
+        return Err("Only uncompressed 24 bit bmps are supported");
+    }
+    if width <= 0 || height == 0 {
+        return Err("Empty bmp");
+    }
+    let (width, bottom_up) = (width as usize, height > 0);
+    let height = height.unsigned_abs() as usize;
+    // Rows are padded to 4 bytes
+    let stride = (width * 3).div_ceil(4) * 4;
+    let data = bytes
+        .get(data_start..data_start + stride * height)
+        .ok_or("Truncated bmp data")?;
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for y in 0..height {
+        let row = if bottom_up { height - 1 - y } else { y };
+        for pixel in data[row * stride..][..width * 3].chunks(3) {
+            let brightness = pixel.iter().map(|&c| c as usize).sum::<usize>() / 3;
+            pixels.push(brightness >= 128);
+        }
+    }
+    Ok(Bitmap {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Draw `bitmap` with half blocks, one string per line of text
+pub fn half_blocks(bitmap: &Bitmap) -> Vec<String> {
+    (0..bitmap.height.div_ceil(2))
+        .map(|row| {
+            (0..bitmap.width)
+                .map(
+                    |x| match (bitmap.get(x, row * 2), bitmap.get(x, row * 2 + 1)) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    },
+                )
+                .collect()
+        })
+        .collect()
+}
+
+/// The art of one symbol
+pub fn symbol_art(symbol: u8) -> Vec<String> {
+    let bitmap = parse_bmp(SYMBOL_BMPS[symbol as usize]).expect("The templates are valid bmps");
+    half_blocks(&bitmap.without_background().cropped())
+}
+
+/// The symbols of `guess` side by side, centered vertically, ending with a newline
+pub fn guess_art(guess: PossibleAnswer) -> String {
+    let arts = guess.map(symbol_art);
+    let height = arts.iter().map(Vec::len).max().unwrap_or(0);
+    let mut out = String::new();
+    for y in 0..height {
+        let mut line = String::new();
+        for art in arts.iter() {
+            let width = art.first().map_or(0, |line| line.chars().count());
+            let top = (height - art.len()) / 2;
+            let text = y
+                .checked_sub(top)
+                .and_then(|y| art.get(y))
+                .map_or("", String::as_str);
+            line += &format!("{:width$}{:GAP$}", text, "");
+        }
+        out += line.trim_end();
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn draws_the_templates() {
+        let tile = parse_bmp(SYMBOL_BMPS[1]).unwrap();
+        assert_eq!((tile.width, tile.height), (25, 26));
+        let symbol = tile.without_background().cropped();
+        assert!(symbol.width < 12 && symbol.height < 20);
+        // Not a tile, so nothing to remove
+        let bitmap = parse_bmp(SYMBOL_BMPS[0]).unwrap();
+        let lit = bitmap.pixels.iter().filter(|&&p| p).count();
+        let bitmap = bitmap.without_background();
+        assert_eq!(bitmap.pixels.iter().filter(|&&p| p).count(), lit);
+
+        // The templates differ, so their art does too
+        for a in 0..SYMBOL_COUNT as u8 {
+            assert!(!symbol_art(a).is_empty());
+            for b in a + 1..SYMBOL_COUNT as u8 {
+                assert_ne!(symbol_art(a), symbol_art(b));
+            }
+        }
+        let art = guess_art([0, 1, 4, 11]);
+        assert!(art.lines().all(|line| line.chars().count() <= 80));
+
+        assert!(parse_bmp(b"BM").is_err());
+        assert!(parse_bmp(&SYMBOL_BMPS[1][..100]).is_err());
+    }
+
+    #[test]
+    fn half_block_art() {
+        let bitmap = Bitmap {
+            width: 3,
+            height: 3,
+            pixels: vec![
+                true, true, false, //
+                true, false, true, //
+                false, false, true, //
+            ],
+        };
+        assert_eq!(half_blocks(&bitmap), vec!["█▀▄", "  ▀"]);
+        let cropped = Bitmap {
+            width: 4,
+            height: 3,
+            pixels: vec![
+                false, false, false, false, //
+                false, true, false, true, //
+                false, false, false, false, //
+            ],
+        }
+        .cropped();
+        assert_eq!((cropped.width, cropped.height), (3, 1));
+        assert_eq!(cropped.pixels, vec![true, false, true]);
+    }
+}
@@ -0,0 +1,205 @@
+//! `console --jsonl`: a JSON lines protocol on stdin/stdout, to drive the solver from other
+//! programs.
+//!
+//! Every line of input is one request object with a `cmd` field, and gets exactly one response
+//! line. Responses always have `v` (The [PROTOCOL_VERSION]) and `ok`. When `ok` is false they
+//! have a `code` saying what went wrong (One of the `ERROR_*` constants) and an `error` message
+//! for people, whose wording can change. An `id` in a request is copied to its response. Requests
+//! can have a `v` too, and are refused if it's newer than the version we speak.
+//!
+//! ```text
+//! {"cmd":"guess"}
+//! {"v":1,"ok":true,"guess":{"code":"0012",...},"candidates":20736,...}
+//! {"cmd":"apply","guess":"0012","result":[1,0],"id":7}
+//! {"v":1,"ok":true,"id":7,"candidates":3458}
+//! {"cmd":"fly"}
+//! {"v":1,"ok":false,"code":"unknown_command","error":"Unknown command 'fly'"}
+//! ```
+//!
+//! Commands:
+//! - `apply` with `guess` (A code like `0a1b`, `?` for unknown symbols, or an array of symbols with
+//!   null for unknown ones) and `result` (`[correct_positions, correct_symbols]`)
+//! - `guess`: the next guess
+//! - `undo`: forget the last applied result
+//! - `reset`: start a new vault
+//! - `candidates` with an optional `limit`: the remaining secrets, most likely first
+//! - `explain`: how the candidates would split for each result of the next guess
+use std::io::{BufRead, Write};
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use solver::{state, PartialAnswer, SolverContext};
+
+use crate::commands::{code_json, guess_json};
+
+/// Bump this whenever a request or response changes in a way that can break clients
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The line isn't JSON
+pub const ERROR_INVALID_JSON: &str = "invalid_json";
+/// The request has no `cmd`, or its fields are missing or have the wrong type
+pub const ERROR_INVALID_REQUEST: &str = "invalid_request";
+pub const ERROR_UNKNOWN_COMMAND: &str = "unknown_command";
+/// The request's `v` is newer than [PROTOCOL_VERSION]
+pub const ERROR_UNSUPPORTED_VERSION: &str = "unsupported_version";
+/// A guess or result the vault can't have
+pub const ERROR_INVALID_ARGUMENT: &str = "invalid_argument";
+/// No secret is consistent with the history, so there is nothing to guess or explain
+pub const ERROR_NO_CANDIDATES: &str = "no_candidates";
+pub const ERROR_NOTHING_TO_UNDO: &str = "nothing_to_undo";
+
+const COMMANDS: [&str; 6] = ["apply", "guess", "undo", "reset", "candidates", "explain"];
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Code {
+    Text(String),
+    Symbols(PartialAnswer),
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "cmd", rename_all = "lowercase")]
+enum Request {
+    Apply { guess: Code, result: (u8, u8) },
+    Guess,
+    Undo,
+    Reset,
+    Candidates { limit: Option<usize> },
+    Explain,
+}
+
+#[derive(Deserialize)]
+struct Envelope {
+    v: Option<u32>,
+    #[serde(flatten)]
+    request: Request,
+}
+
+/// Why a request failed: one of the `ERROR_*` codes and a message
+type Error = (&'static str, String);
+
+fn invalid_argument(message: impl Into<String>) -> Error {
+    (ERROR_INVALID_ARGUMENT, message.into())
+}
+
+fn no_candidates() -> Error {
+    (
+        ERROR_NO_CANDIDATES,
+        "No secret is consistent with the history".to_string(),
+    )
+}
+
+/// Handle one request, returning the fields of the response other than `v`, `ok` and `id`
+fn handle(ctx: &mut SolverContext, request: Value) -> Result<Value, Error> {
+    if let Some(cmd) = request.get("cmd").and_then(Value::as_str) {
+        if !COMMANDS.contains(&cmd) {
+            return Err((ERROR_UNKNOWN_COMMAND, format!("Unknown command '{}'", cmd)));
+        }
+    }
+    let envelope = serde_json::from_value::<Envelope>(request)
+        .map_err(|e| (ERROR_INVALID_REQUEST, e.to_string()))?;
+    if envelope.v.is_some_and(|v| v > PROTOCOL_VERSION) {
+        return Err((
+            ERROR_UNSUPPORTED_VERSION,
+            format!(
+                "Unsupported protocol version, this is version {}",
+                PROTOCOL_VERSION
+            ),
+        ));
+    }
+    Ok(match envelope.request {
+        Request::Apply { guess, result } => {
+            let guess = match guess {
+                Code::Text(text) => state::parse_partial_answer(&text)
+                    .ok_or_else(|| invalid_argument(format!("Invalid guess '{}'", text)))?,
+                Code::Symbols(symbols) => symbols,
+            };
+            if guess
+                .iter()
+                .flatten()
+                .any(|&symbol| symbol as usize >= solver::SYMBOL_COUNT)
+            {
+                return Err(invalid_argument("Symbols go from 0 to 11"));
+            }
+            crate::repl::check_result(result.0, result.1).map_err(invalid_argument)?;
+            ctx.apply_partial_result(guess, result.0, result.1);
+            json!({ "candidates": ctx.remaining_count() })
+        }
+        Request::Guess => {
+            if ctx.remaining_count() == 0 {
+                return Err(no_candidates());
+            }
+            let guess = ctx.guess();
+            guess_json(ctx, guess)
+        }
+        Request::Undo => {
+            let (guess, p, s) = crate::repl::undo(ctx)
+                .ok_or((ERROR_NOTHING_TO_UNDO, "Nothing to undo".to_string()))?;
+            json!({
+                "undone": { "guess": state::format_partial_answer(guess), "result": [p, s] },
+                "candidates": ctx.remaining_count(),
+            })
+        }
+        Request::Reset => {
+            ctx.reset();
+            json!({ "candidates": ctx.remaining_count() })
+        }
+        Request::Candidates { limit } => {
+            let ranked = ctx.rank_candidates();
+            json!({
+                "count": ranked.len(),
+                "candidates": ranked
+                    .iter()
+                    .take(limit.unwrap_or(usize::MAX))
+                    .map(|candidate| json!({
+                        "secret": code_json(candidate.secret),
+                        "probability": candidate.probability,
+                    }))
+                    .collect::<Vec<_>>(),
+            })
+        }
+        Request::Explain => {
+            if ctx.remaining_count() == 0 {
+                return Err(no_candidates());
+            }
+            let guess = ctx.guess();
+            let mut explanation = guess_json(ctx, guess);
+            explanation["consistent"] = ctx.is_consistent(guess).into();
+            explanation["results"] = crate::repl::result_split(ctx, guess)
+                .iter()
+                .map(|&((p, s), n)| json!({ "result": [p, s], "candidates": n }))
+                .collect::<Vec<_>>()
+                .into();
+            explanation
+        }
+    })
+}
+
+/// Answer requests from `input` until it ends
+pub fn run(input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+    let mut ctx = SolverContext::new();
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request = serde_json::from_str::<Value>(&line);
+        // The id is echoed even for requests we couldn't understand, if it can be found
+        let id = request
+            .as_ref()
+            .ok()
+            .and_then(|request| request.get("id").cloned());
+        let mut response = request
+            .map_err(|e| (ERROR_INVALID_JSON, e.to_string()))
+            .and_then(|request| handle(&mut ctx, request))
+            .unwrap_or_else(|(code, e)| json!({ "code": code, "error": e }));
+        response["v"] = PROTOCOL_VERSION.into();
+        response["ok"] = response.get("error").is_none().into();
+        if let Some(id) = id {
+            response["id"] = id;
+        }
+        writeln!(output, "{}", response)?;
+        output.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,363 @@
+//! Symbol names, prompts and messages of the console, so players can use their own nicknames for
+//! the symbols or another language.
+//!
+//! `console --locale <file> ...` loads them from a TOML file, or JSON if the file name ends with
+//! `.json`. Everything is optional, what's missing keeps the built-in English text:
+//!
+//! ```toml
+//! # Short names, shown in guesses and accepted when typing them. Either all 12 or none
+//! names = ["J", "B", "C", "F", "><", "M", "P", "Sablier", "S", "T", "|X|", "X"]
+//! # Shown under each suggestion
+//! descriptions = ["Un J à l'envers", ...]
+//!
+//! [messages]
+//! solved = "Trouvé en {guesses} coups ! `new` pour un autre coffre"
+//! practice_prompt = "Essai {number}/{guesses}> "
+//! ```
+//!
+//! Messages can use the `{placeholders}` of their default, see [Messages]. They cover what the
+//! commands, `console practice` and the terminal UI print, but not the usage of the command line
+//! arguments.
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use serde::Deserialize;
+use solver::SYMBOL_COUNT;
+
+use crate::symbols::{SYMBOL_IDX_TO_LETTER, SYMBOL_SHORT_NAMES};
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Locale {
+    pub names: [String; SYMBOL_COUNT],
+    pub descriptions: [String; SYMBOL_COUNT],
+    pub messages: Messages,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Messages {
+    /// Before each command
+    pub prompt: String,
+    /// `{number}`, `{guess}`, `{candidates}` and `{attempts}`
+    pub suggestion: String,
+    pub no_candidates: String,
+    /// After [Messages::no_candidates], once per way to fix the results. `{repair}`
+    pub maybe: String,
+    /// A symbol to fix in a repair. `{slot}` and `{guess}` (Their numbers) and `{symbol}`
+    pub repair_symbol: String,
+    /// A result to fix in a repair. `{guess}` (Its number), `{positions}` and `{symbols}`
+    pub repair_result: String,
+    /// Between the fixes of a repair
+    pub repair_and: String,
+    /// `{guesses}`
+    pub solved: String,
+    pub new_vault: String,
+    /// After entering the guess that was played instead. `{guess}`
+    pub playing: String,
+    /// After `undo`. `{guess}`, `{positions}` and `{symbols}`
+    pub forgot: String,
+    /// Before the list of `candidates`. `{candidates}`
+    pub candidates: String,
+    /// In `explain` and the terminal UI. `{candidates}` and `{attempts}`
+    pub remaining: String,
+    /// `{guess}` and `{probability}` (A percentage)
+    pub explain_win: String,
+    /// `{guess}`, `{worst}` and `{average}` (Candidates left after it)
+    pub explain_consistent: String,
+    /// Like [Messages::explain_consistent]
+    pub explain_inconsistent: String,
+    /// Once per result of the guess. `{positions}`, `{symbols}` and `{candidates}`
+    pub explain_result: String,
+    /// `{guesses}` and `{file}`
+    pub saved: String,
+    /// `{guesses}` and `{file}`
+    pub loaded: String,
+    /// `{line}` and `{entry}` (The line as written in the file)
+    pub contradiction: String,
+    /// `{name}`
+    pub session_started: String,
+    /// `{name}`
+    pub session_already: String,
+    /// `{name}` and `{status}`
+    pub session_status: String,
+    /// Status of a vault in `sessions`. `{guesses}` and `{candidates}`
+    pub status: String,
+    /// `{guesses}`
+    pub status_solved: String,
+    /// `{guesses}`
+    pub status_no_candidates: String,
+    /// `{error}`
+    pub error: String,
+    pub already_solved: String,
+    pub nothing_guessed: String,
+    pub nothing_to_undo: String,
+    pub nothing_to_explain: String,
+    /// `{text}`
+    pub not_a_number: String,
+    pub expected_result: String,
+    /// `{max}`, `{positions}` and `{symbols}`
+    pub result_too_big: String,
+    /// `{positions}`
+    pub result_impossible: String,
+    /// `{command}` (`save` or `load`)
+    pub expected_file: String,
+    pub expected_session: String,
+    /// `{command}`
+    pub unknown_command: String,
+    /// `{expected}` and `{got}`
+    pub symbol_count: String,
+    /// `{symbol}` and `{names}`
+    pub unknown_symbol: String,
+    /// `{file}` and `{error}`
+    pub read_failed: String,
+    /// `{file}` and `{error}`
+    pub write_failed: String,
+    /// An error in a line of a saved vault. `{line}` and `{error}`
+    pub line_error: String,
+    /// The `help` command. `{names}` are the symbol names
+    pub help: String,
+    /// `{guesses}` and `{example}`, a guess written with the symbol names
+    pub practice_start: String,
+    /// `{number}` and `{guesses}`
+    pub practice_prompt: String,
+    /// `{guesses}`
+    pub practice_found: String,
+    /// `{secret}`
+    pub practice_lost: String,
+    /// Before the solver's guesses. `{guesses}`
+    pub practice_replay: String,
+    /// `{seed}`
+    pub practice_seed: String,
+    /// Titles of the terminal UI
+    pub tui_output: String,
+    pub tui_command: String,
+    /// `{name}`
+    pub tui_board: String,
+    pub tui_heatmap: String,
+    /// `{candidates}`
+    pub tui_candidates: String,
+    pub tui_no_candidates: String,
+    /// `{limit}`
+    pub tui_too_many: String,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Self {
+            names: SYMBOL_SHORT_NAMES.map(str::to_string),
+            descriptions: SYMBOL_IDX_TO_LETTER.map(str::to_string),
+            messages: Messages::default(),
+        }
+    }
+}
+
+impl Default for Messages {
+    fn default() -> Self {
+        Self {
+            prompt: "> ".to_string(),
+            suggestion:
+                "Guess {number}: {guess}   ({candidates} candidates, {attempts} attempts left)"
+                    .to_string(),
+            no_candidates:
+                "No secret matches these results, something was probably entered wrong. Use `undo`"
+                    .to_string(),
+            maybe: "  Maybe: {repair}".to_string(),
+            repair_symbol: "symbol {slot} of guess {guess} was {symbol}".to_string(),
+            repair_result: "the result of guess {guess} was {positions},{symbols}".to_string(),
+            repair_and: " and ".to_string(),
+            solved: "Solved in {guesses} guesses! Use `new` for another vault".to_string(),
+            new_vault: "New vault".to_string(),
+            playing: "The next result applies to {guess}".to_string(),
+            forgot: "Forgot {guess}: {positions},{symbols}".to_string(),
+            candidates: "{candidates} candidates".to_string(),
+            remaining: "{candidates} candidates and {attempts} attempts left".to_string(),
+            explain_win:
+                "Going for the win: {guess} is the secret with probability {probability}%"
+                    .to_string(),
+            explain_consistent:
+                "{guess} could be the secret. In the worst case it leaves {worst} candidates, {average} on average"
+                    .to_string(),
+            explain_inconsistent:
+                "{guess} can't be the secret. In the worst case it leaves {worst} candidates, {average} on average"
+                    .to_string(),
+            explain_result: "  {positions},{symbols} -> {candidates} candidates".to_string(),
+            saved: "Saved {guesses} guesses to {file}".to_string(),
+            loaded: "Loaded {guesses} guesses from {file}".to_string(),
+            contradiction:
+                "Line {line} ({entry}) contradicts the lines before it: no secret matches all of them"
+                    .to_string(),
+            session_started: "Started session {name}".to_string(),
+            session_already: "Already in session {name}".to_string(),
+            session_status: "Session {name}: {status}".to_string(),
+            status: "{guesses} guesses, {candidates} candidates".to_string(),
+            status_solved: "solved in {guesses} guesses".to_string(),
+            status_no_candidates:
+                "{guesses} guesses, no candidates (Something was entered wrong)".to_string(),
+            error: "Error: {error}".to_string(),
+            already_solved: "Already solved, use `new` or `undo`".to_string(),
+            nothing_guessed: "Nothing was guessed, enter the guess you played with `guess`"
+                .to_string(),
+            nothing_to_undo: "Nothing to undo".to_string(),
+            nothing_to_explain: "There's no guess to explain".to_string(),
+            not_a_number: "'{text}' is not a number".to_string(),
+            expected_result: "Expected 2 numbers, like `result 2,1`".to_string(),
+            result_too_big: "The 2 numbers add up to at most {max}, got {positions},{symbols}"
+                .to_string(),
+            result_impossible:
+                "{positions},1 is impossible: if {positions} symbols are in place the last one can't be elsewhere"
+                    .to_string(),
+            expected_file: "Expected a file name, like `{command} vault.txt`".to_string(),
+            expected_session: "Expected a session name, like `session library`".to_string(),
+            unknown_command: "Unknown command '{command}', try `help`".to_string(),
+            symbol_count: "Expected {expected} symbols but got {got}".to_string(),
+            unknown_symbol: "Unknown symbol '{symbol}'. Use 0-11 or one of: {names}".to_string(),
+            read_failed: "Couldn't read {file}: {error}".to_string(),
+            write_failed: "Couldn't write {file}: {error}".to_string(),
+            line_error: "Line {line}: {error}".to_string(),
+            help: crate::repl::HELP.to_string(),
+            practice_start:
+                "Find the secret in {guesses} guesses. Enter guesses like `{example}` or `0a1b`, `quit` gives up"
+                    .to_string(),
+            practice_prompt: "Guess {number}/{guesses}> ".to_string(),
+            practice_found: "Found it in {guesses} guesses!".to_string(),
+            practice_lost: "The secret was {secret}".to_string(),
+            practice_replay: "The solver would have found it in {guesses}:".to_string(),
+            practice_seed: "Seed: {seed}".to_string(),
+            tui_output: "Output".to_string(),
+            tui_command: "Command (help lists them, Esc quits)".to_string(),
+            tui_board: "Board (session {name})".to_string(),
+            tui_heatmap: "Symbol per slot".to_string(),
+            tui_candidates: "Candidates ({candidates})".to_string(),
+            tui_no_candidates: "No secret matches these results, use `undo`".to_string(),
+            tui_too_many: "Listed once at most {limit} are left".to_string(),
+        }
+    }
+}
+
+impl Locale {
+    /// Parse a locale file, JSON if `json` and TOML otherwise
+    pub fn parse(text: &str, json: bool) -> Result<Self, String> {
+        let locale: Locale = if json {
+            serde_json::from_str(text).map_err(|e| e.to_string())?
+        } else {
+            toml::from_str(text).map_err(|e| e.to_string())?
+        };
+        locale.validate()?;
+        Ok(locale)
+    }
+    pub fn load(path: &str) -> Result<Self, String> {
+        let text =
+            std::fs::read_to_string(path).map_err(|e| format!("Couldn't read {}: {}", path, e))?;
+        Self::parse(&text, path.to_ascii_lowercase().ends_with(".json"))
+            .map_err(|e| format!("Invalid locale file {}: {}", path, e))
+    }
+    /// Names have to be usable when typing guesses
+    fn validate(&self) -> Result<(), String> {
+        for (idx, name) in self.names.iter().enumerate() {
+            if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == ',') {
+                return Err(format!(
+                    "The name of symbol {} ('{}') must be a single word without commas",
+                    idx, name
+                ));
+            }
+            // Symbols can also be typed by index
+            if name.parse::<u8>().is_ok() {
+                return Err(format!(
+                    "The name of symbol {} ('{}') can't be a number",
+                    idx, name
+                ));
+            }
+            if let Some(other) = self.names[..idx]
+                .iter()
+                .position(|other| other.eq_ignore_ascii_case(name))
+            {
+                return Err(format!(
+                    "Symbols {} and {} have the same name '{}'",
+                    other, idx, name
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+static LOCALE: OnceLock<Locale> = OnceLock::new();
+
+/// The locale in use, the built-in one unless [set] was called first
+pub fn get() -> &'static Locale {
+    LOCALE.get_or_init(Locale::default)
+}
+
+/// Use `locale` from now on. Only works before the locale is first used
+pub fn set(locale: Locale) -> Result<(), String> {
+    LOCALE
+        .set(locale)
+        .map_err(|_| "The locale is already in use".to_string())
+}
+
+/// Replace the `{name}` placeholders of `template`
+pub fn fill(template: &str, values: &[(&str, &dyn Display)]) -> String {
+    values
+        .iter()
+        .fold(template.to_string(), |text, (name, value)| {
+            text.replace(&format!("{{{}}}", name), &value.to_string())
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_locale_files() {
+        let toml = r#"
+            names = ["J", "B", "C", "F", "><", "M", "P", "Sablier", "S", "T", "|X|", "X"]
+            [messages]
+            solved = "Trouvé en {guesses} coups !"
+            playing = "Le prochain résultat compte pour {guess}"
+            practice_prompt = "Essai {number}/{guesses}> "
+            tui_board = "Plateau (session {name})"
+        "#;
+        let locale = Locale::parse(toml, false).unwrap();
+        assert_eq!(locale.names[7], "Sablier");
+        assert_eq!(locale.descriptions, Locale::default().descriptions);
+        assert_eq!(
+            fill(&locale.messages.solved, &[("guesses", &5)]),
+            "Trouvé en 5 coups !"
+        );
+        assert_eq!(
+            fill(&locale.messages.playing, &[("guess", &"J J B C")]),
+            "Le prochain résultat compte pour J J B C"
+        );
+        assert_eq!(
+            fill(
+                &locale.messages.practice_prompt,
+                &[("number", &3), ("guesses", &12)]
+            ),
+            "Essai 3/12> "
+        );
+        assert_eq!(
+            fill(&locale.messages.tui_board, &[("name", &"main")]),
+            "Plateau (session main)"
+        );
+        assert_eq!(locale.messages.new_vault, "New vault");
+        assert_eq!(locale.messages.tui_output, "Output");
+
+        let json = r#"{ "messages": { "prompt": "? " } }"#;
+        let locale = Locale::parse(json, true).unwrap();
+        assert_eq!(locale.messages.prompt, "? ");
+        assert_eq!(locale.names, Locale::default().names);
+
+        for bad in [
+            r#"names = ["J"]"#,
+            r#"nmaes = []"#,
+            "[messages]\nplayed = \"{guess}\"",
+            r#"names = ["J", "B", "C", "F", "><", "M", "P", "H", "S", "T", "|X|", "j"]"#,
+            r#"names = ["J", "B", "C", "F", "><", "M", "P", "H", "S", "T", "|X|", "1"]"#,
+            r#"names = ["J", "B", "C", "F", "><", "M", "P", "H", "S", "T", "|X|", "X X"]"#,
+        ] {
+            assert!(Locale::parse(bad, false).is_err(), "{}", bad);
+        }
+    }
+}
@@ -1,53 +1,121 @@
-use text_io::read;
-
 use solver::SolverContext;
 
-const SYMBOL_IDX_TO_LETTER: [&'static str; solver::SYMBOL_COUNT] = [
-    "Inverted J? The first symbol",
-    "B",
-    "C",
-    "F",
-    "> and < on top of each other (NOT the X, they cross!)",
-    "M",
-    "P",
-    "The thing between P and S under B",
-    "S (Jagged, like a thunder)",
-    "T (Like a cross, the upper line has an angle downwards)",
-    "|X| (Between the T and X)",
-    "X",
-];
+mod commands;
+mod glyphs;
+mod jsonl;
+mod locale;
+mod practice;
+mod repl;
+mod symbols;
+#[cfg(feature = "tui")]
+mod tui;
+
+/// `console cheatsheet [depth] [--html]` prints a cheat sheet to follow the solver on paper
+fn print_cheatsheet(args: &[String]) {
+    let html = args.iter().any(|arg| arg == "--html");
+    let depth = args
+        .iter()
+        .find_map(|arg| arg.parse::<usize>().ok())
+        // The first 3 guesses are always the same, the 4th is the first one that depends on results
+        .unwrap_or(4);
+
+    let tree = solver::tree::TreeNode::build(&SolverContext::new(), depth);
+    let locale = locale::get();
+    let names = solver::cheatsheet::SymbolNames {
+        names: &locale.names.each_ref().map(String::as_str),
+        descriptions: &locale.descriptions.each_ref().map(String::as_str),
+    };
+    if html {
+        print!("{}", solver::cheatsheet::to_html(&tree, &names));
+    } else {
+        print!("{}", solver::cheatsheet::to_markdown(&tree, &names));
+    }
+}
 
 fn main() {
-    let mut state = SolverContext::new();
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    // Symbol names and messages, for every mode
+    if args.first().map(String::as_str) == Some("--locale") {
+        let loaded = match args.get(1) {
+            Some(path) => locale::Locale::load(path),
+            None => Err("--locale expects a file".to_string()),
+        };
+        if let Err(e) = loaded.and_then(locale::set) {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+        args.drain(..2);
+    }
+    if args.first().map(String::as_str) == Some("cheatsheet") {
+        print_cheatsheet(&args[1..]);
+        return;
+    }
+    if let Some(command) = args
+        .first()
+        .filter(|command| commands::COMMANDS.contains(&command.as_str()))
+    {
+        match commands::run(command, &args[1..]) {
+            Ok(out) => println!("{}", out),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(e.exit_code());
+            }
+        }
+        return;
+    }
+    if args.first().map(String::as_str) == Some("practice") {
+        if let Err(e) = practice::run(&args[1..]) {
+            eprintln!("{}", e);
+            std::process::exit(2);
+        }
+        return;
+    }
 
-    loop {
-        state.reset();
-        loop {
-            let guess = state.guess();
-            println!(
-                "Please try this guess: \n\t{:?}",
-                guess
-                    .iter()
-                    .map(|&symbol_idx| SYMBOL_IDX_TO_LETTER[symbol_idx as usize])
-                    .collect::<Vec<_>>()
-            );
-            println!("What was the result? (Enter the 2 numbers, press enter after each)");
-            let one: u8 = read!();
-            if one == 4 {
-                println!("Found it!");
-                println!("Press Q to quit, anything else to guess another puzzle");
-                let mut line = String::new();
-                let stdin = std::io::stdin();
-                std::io::BufRead::read_line(&mut stdin.lock(), &mut line).unwrap();
-                drop(stdin);
-                if line == "Q" {
-                    return;
-                }
-                break;
+    let mut repl = repl::Repl::new();
+    match args.as_slice() {
+        [] => {}
+        [flag] if flag == "--jsonl" => {
+            let stdin = std::io::stdin();
+            if let Err(e) = jsonl::run(stdin.lock(), std::io::stdout().lock()) {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+            return;
+        }
+        #[cfg(feature = "tui")]
+        [flag] if flag == "--tui" => {
+            if let Err(e) = tui::run(repl) {
+                eprintln!("{}", e);
+                std::process::exit(1);
             }
-            let two: u8 = read!();
-            println!("({}, {})", one, two);
-            state.apply_result(guess, one, two);
+            return;
+        }
+        #[cfg(not(feature = "tui"))]
+        [flag] if flag == "--tui" => {
+            eprintln!("This console was built without the TUI, build it with `--features tui`");
+            std::process::exit(2);
         }
+        // Continue a vault saved with the `save` command
+        [flag, path] if flag == "--resume" => {
+            let mut out = String::new();
+            let loaded = repl.load(path, &mut out);
+            print!("{}", out);
+            if let Err(e) = loaded {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        _ => {
+            eprintln!(
+                "Usage: console [--locale <file>] [--resume <file> | --jsonl | --tui]\n       console cheatsheet [depth] [--html]\n       console practice [--seed <n> | --secret <code>]\n       console next|candidates|solve|eval [options], see docs.md\n--locale <file> also goes before the other commands"
+            );
+            std::process::exit(2);
+        }
+    }
+
+    let stdin = std::io::stdin();
+    if let Err(e) = repl.run(stdin.lock(), std::io::stdout().lock()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
     }
 }
@@ -0,0 +1,187 @@
+//! `console practice`: play a vault against a known secret, without burning a real one.
+//!
+//! The player has the same [solver::MAX_GUESSES] guesses as in the game, and each one is scored
+//! with [solver::compare]. At the end we show how the solver would have played the same secret,
+//! to compare.
+use std::io::{BufRead, Write};
+
+use solver::{compare, PossibleAnswer, SolverContext, Strategy, ANSWER_SIZE, MAX_GUESSES};
+
+use crate::locale::{self, fill};
+use crate::repl::error_message;
+use crate::symbols::*;
+
+/// The guesses `strategy` makes for `secret` and the result of each, ending with the secret
+pub fn solver_replay(strategy: Strategy, secret: PossibleAnswer) -> Vec<(PossibleAnswer, u8, u8)> {
+    let mut ctx = SolverContext::with_strategy(strategy);
+    let mut replay = Vec::new();
+    loop {
+        let guess = ctx.guess();
+        let (p, s) = compare(&[secret, guess]);
+        replay.push((guess, p, s));
+        if guess == secret {
+            return replay;
+        }
+        ctx.apply_result(guess, p, s);
+    }
+}
+
+/// Play against `secret` until it's found, the guesses run out, the player gives up or the input
+/// ends. Returns the number of guesses it took, None if it wasn't found
+pub fn play(
+    secret: PossibleAnswer,
+    input: impl BufRead,
+    mut output: impl Write,
+) -> std::io::Result<Option<usize>> {
+    let messages = &locale::get().messages;
+    writeln!(
+        output,
+        "{}",
+        fill(
+            &messages.practice_start,
+            &[
+                ("guesses", &MAX_GUESSES),
+                ("example", &format_guess([0, 1, 4, 11]))
+            ]
+        )
+    )?;
+    let mut lines = input.lines();
+    let mut guesses = 0;
+    let mut found = None;
+    while guesses < MAX_GUESSES {
+        write!(
+            output,
+            "{}",
+            fill(
+                &messages.practice_prompt,
+                &[("number", &(guesses + 1)), ("guesses", &MAX_GUESSES)]
+            )
+        )?;
+        output.flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => {
+                writeln!(output)?;
+                break;
+            }
+        };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.eq_ignore_ascii_case("quit") || line.eq_ignore_ascii_case("q") {
+            break;
+        }
+        let guess = match parse_code(line) {
+            Ok(guess) => guess,
+            Err(e) => {
+                writeln!(output, "{}", error_message(&e))?;
+                continue;
+            }
+        };
+        guesses += 1;
+        let (p, s) = compare(&[secret, guess]);
+        writeln!(output, "{}: {},{}", format_guess(guess), p, s)?;
+        if p as usize == ANSWER_SIZE {
+            found = Some(guesses);
+            break;
+        }
+    }
+
+    match found {
+        Some(guesses) => writeln!(
+            output,
+            "{}",
+            fill(&messages.practice_found, &[("guesses", &guesses)])
+        )?,
+        None => writeln!(
+            output,
+            "{}",
+            fill(
+                &messages.practice_lost,
+                &[("secret", &format_guess(secret))]
+            )
+        )?,
+    }
+    let replay = solver_replay(Strategy::default(), secret);
+    writeln!(
+        output,
+        "{}",
+        fill(&messages.practice_replay, &[("guesses", &replay.len())])
+    )?;
+    for (i, (guess, p, s)) in replay.iter().enumerate() {
+        writeln!(
+            output,
+            "  {:>2}. {:<24} {},{}",
+            i + 1,
+            format_guess(*guess),
+            p,
+            s
+        )?;
+    }
+    Ok(found)
+}
+
+/// `console practice [--seed <n> | --secret <code>]`. Without arguments the secret is random
+pub fn run(args: &[String]) -> Result<(), String> {
+    let secret = match args {
+        [] => {
+            // Shown so the same secret can be played again
+            let seed = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos() as u64);
+            println!(
+                "{}",
+                fill(&locale::get().messages.practice_seed, &[("seed", &seed)])
+            );
+            solver::genetic::SplitMix64::new(seed).answer()
+        }
+        [flag, seed] if flag == "--seed" => {
+            let seed = seed
+                .parse::<u64>()
+                .map_err(|_| format!("Invalid seed '{}'", seed))?;
+            solver::genetic::SplitMix64::new(seed).answer()
+        }
+        [flag, secret] if flag == "--secret" => parse_code(secret)?,
+        _ => return Err("Usage: console practice [--seed <n> | --secret <code>]".to_string()),
+    };
+    let stdin = std::io::stdin();
+    play(secret, stdin.lock(), std::io::stdout().lock()).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn replay_finds_the_secret() {
+        let secret = [3, 4, 5, 1];
+        let replay = solver_replay(Strategy::default(), secret);
+        assert_eq!(replay.last(), Some(&(secret, 4, 0)));
+        assert!(replay.len() <= MAX_GUESSES);
+    }
+
+    #[test]
+    fn scores_guesses() {
+        let secret = [3, 4, 5, 1];
+        let mut output = Vec::new();
+        let input = "0012\nnonsense\n\n3 4 5 B\n";
+        assert_eq!(
+            play(secret, input.as_bytes(), &mut output).unwrap(),
+            Some(2)
+        );
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("J J B C: 0,1"));
+        assert!(output.contains("Error: "));
+        assert!(output.contains("Found it in 2 guesses!"));
+
+        // Running out of guesses
+        let input = "0000\n".repeat(MAX_GUESSES + 1);
+        let mut output = Vec::new();
+        assert_eq!(play(secret, input.as_bytes(), &mut output).unwrap(), None);
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(output.matches("J J J J: 0,0").count(), MAX_GUESSES);
+        assert!(output.contains("The secret was F >< M B"));
+    }
+}
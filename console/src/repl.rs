@@ -0,0 +1,735 @@
+//! The interactive command loop of the console. Every line of input is one command (See [HELP]),
+//! so sessions can also be scripted by piping commands in. What it prints comes from the
+//! [crate::locale].
+//!
+//! Several vaults can be followed at once, each in its own named session with its own history.
+//! `session <name>` switches to one (Creating it the first time) and `sessions` lists them. The
+//! other commands apply to the current session.
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::{BufRead, Write};
+
+use solver::{
+    compare, state::SolverState, PartialAnswer, PossibleAnswer, SolverContext, ANSWER_SIZE,
+};
+
+use crate::locale::{self, fill};
+use crate::symbols::*;
+
+pub const HELP: &str = "\
+Commands:
+  guess                Show the suggested guess again
+  guess <symbols>      Record that you played a different guess, like `guess J B >< 11`
+  result <p>,<s>       Apply the result of the guess: correct positions, then correct symbols
+  undo                 Forget the last result
+  candidates [n]       List the most likely remaining secrets (10 by default)
+  explain              Show why the suggested guess was picked
+  new                  Start a new vault
+  save <file>          Save the guesses and results so far to a text file
+  load <file>          Continue a vault saved with `save`
+  session <name>       Switch to another vault, starting it if it's new
+  sessions             List the vaults with their status
+  help                 Show this help
+  quit                 Exit (The end of the input works too)
+Symbols are numbers from 0 to 11 or short names: {names}";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Continue,
+    Quit,
+}
+
+/// The session the console starts in
+pub const FIRST_SESSION: &str = "main";
+
+/// A vault put aside by switching to another session
+struct Session {
+    ctx: SolverContext,
+    pending: Option<PossibleAnswer>,
+}
+
+pub struct Repl {
+    ctx: SolverContext,
+    /// The guess the next result applies to: the suggestion, or the guess the user said they
+    /// played instead. None if there's nothing to guess
+    pending: Option<PossibleAnswer>,
+    /// Name of the current session
+    session: String,
+    /// The other sessions, by name
+    others: BTreeMap<String, Session>,
+}
+
+/// Parse a result like `2,1` or `2 1`: correct positions, then correct symbols
+pub fn parse_result(text: &str) -> Result<(u8, u8), String> {
+    let parts = text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    let messages = &locale::get().messages;
+    let (p, s) = match parts.as_slice() {
+        [p, s] => (
+            p.parse::<u8>()
+                .map_err(|_| fill(&messages.not_a_number, &[("text", p)]))?,
+            s.parse::<u8>()
+                .map_err(|_| fill(&messages.not_a_number, &[("text", s)]))?,
+        ),
+        _ => return Err(messages.expected_result.clone()),
+    };
+    check_result(p, s)?;
+    Ok((p, s))
+}
+
+/// Whether the vault can give this result
+pub fn check_result(p: u8, s: u8) -> Result<(), String> {
+    let messages = &locale::get().messages;
+    let size = ANSWER_SIZE as u8;
+    if p as usize + s as usize > ANSWER_SIZE {
+        return Err(fill(
+            &messages.result_too_big,
+            &[("max", &size), ("positions", &p), ("symbols", &s)],
+        ));
+    }
+    if p == size - 1 && s == 1 {
+        return Err(fill(&messages.result_impossible, &[("positions", &p)]));
+    }
+    Ok(())
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Self {
+            ctx: SolverContext::new(),
+            pending: None,
+            session: FIRST_SESSION.to_string(),
+            others: BTreeMap::new(),
+        }
+    }
+    #[cfg(feature = "tui")]
+    pub fn ctx(&self) -> &SolverContext {
+        &self.ctx
+    }
+    /// The guess the next result applies to, None if there's nothing to guess
+    #[cfg(feature = "tui")]
+    pub fn pending(&self) -> Option<PossibleAnswer> {
+        self.pending
+    }
+    #[cfg(feature = "tui")]
+    pub fn session(&self) -> &str {
+        &self.session
+    }
+    fn solved(&self) -> bool {
+        solved(&self.ctx)
+    }
+    /// Compute the next guess and show it, or explain why there's none
+    fn suggest(&mut self, out: &mut String) {
+        self.pending = None;
+        if self.solved() {
+            return;
+        }
+        if self.ctx.remaining_count() == 0 {
+            let messages = &locale::get().messages;
+            let _ = writeln!(out, "{}", messages.no_candidates);
+            for repair in solver::consistency::find_repairs(self.ctx.history(), 3) {
+                let _ = writeln!(
+                    out,
+                    "{}",
+                    fill(&messages.maybe, &[("repair", &format_repair(&repair))])
+                );
+            }
+            return;
+        }
+        let guess = self.ctx.guess();
+        self.pending = Some(guess);
+        let _ = writeln!(
+            out,
+            "{}",
+            fill(
+                &locale::get().messages.suggestion,
+                &[
+                    ("number", &(self.ctx.history().len() + 1)),
+                    ("guess", &format_guess(guess)),
+                    ("candidates", &self.ctx.remaining_count()),
+                    ("attempts", &self.ctx.attempts_left()),
+                ]
+            )
+        );
+        out.push_str(&crate::glyphs::guess_art(guess));
+        for &symbol in guess.iter() {
+            let _ = writeln!(out, "\t{}", locale::get().descriptions[symbol as usize]);
+        }
+    }
+    /// Show the first suggestion. Call this before the first command
+    pub fn start(&mut self, out: &mut String) {
+        self.suggest(out);
+    }
+    /// Run one line of input. Errors are meant to be shown to the user, and leave the state as
+    /// it was
+    pub fn execute(&mut self, line: &str, out: &mut String) -> Result<Flow, String> {
+        let line = line.trim();
+        let (command, args) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let args = args.trim();
+        let messages = &locale::get().messages;
+        match command.to_ascii_lowercase().as_str() {
+            "" => {}
+            "guess" | "g" => {
+                if args.is_empty() {
+                    self.suggest(out);
+                } else {
+                    let guess = parse_guess(args)?;
+                    self.pending = Some(guess);
+                    let _ = writeln!(
+                        out,
+                        "{}",
+                        fill(&messages.playing, &[("guess", &format_guess(guess))])
+                    );
+                }
+            }
+            "result" | "r" => {
+                if self.solved() {
+                    return Err(messages.already_solved.clone());
+                }
+                let (p, s) = parse_result(args)?;
+                let guess = self
+                    .pending
+                    .ok_or_else(|| messages.nothing_guessed.clone())?;
+                self.ctx.apply_result(guess, p, s);
+                if self.solved() {
+                    let _ = writeln!(
+                        out,
+                        "{}",
+                        fill(&messages.solved, &[("guesses", &self.ctx.history().len())])
+                    );
+                }
+                self.suggest(out);
+            }
+            "undo" | "u" => {
+                let (guess, p, s) =
+                    undo(&mut self.ctx).ok_or_else(|| messages.nothing_to_undo.clone())?;
+                let _ = writeln!(
+                    out,
+                    "{}",
+                    fill(
+                        &messages.forgot,
+                        &[
+                            ("guess", &format_partial_guess(guess)),
+                            ("positions", &p),
+                            ("symbols", &s)
+                        ]
+                    )
+                );
+                self.suggest(out);
+            }
+            "candidates" | "c" => {
+                let limit = if args.is_empty() {
+                    10
+                } else {
+                    args.parse::<usize>()
+                        .map_err(|_| fill(&messages.not_a_number, &[("text", &args)]))?
+                };
+                let ranked = self.ctx.rank_candidates();
+                let _ = writeln!(
+                    out,
+                    "{}",
+                    fill(&messages.candidates, &[("candidates", &ranked.len())])
+                );
+                for candidate in ranked.iter().take(limit) {
+                    let _ = writeln!(
+                        out,
+                        "  {:<24} {:.2}%",
+                        format_guess(candidate.secret),
+                        candidate.probability * 100.0
+                    );
+                }
+            }
+            "explain" | "e" => self.explain(out)?,
+            "new" | "n" => {
+                self.ctx.reset();
+                let _ = writeln!(out, "{}", messages.new_vault);
+                self.suggest(out);
+            }
+            "save" => {
+                if args.is_empty() {
+                    return Err(fill(&messages.expected_file, &[("command", &"save")]));
+                }
+                self.save(args)?;
+                let _ = writeln!(
+                    out,
+                    "{}",
+                    fill(
+                        &messages.saved,
+                        &[("guesses", &self.ctx.history().len()), ("file", &args)]
+                    )
+                );
+            }
+            "load" => {
+                if args.is_empty() {
+                    return Err(fill(&messages.expected_file, &[("command", &"load")]));
+                }
+                self.load(args, out)?;
+                self.suggest(out);
+            }
+            "session" => {
+                if args.is_empty() || args.contains(char::is_whitespace) {
+                    return Err(messages.expected_session.clone());
+                }
+                self.switch(args, out);
+            }
+            "sessions" => {
+                let mut sessions = self
+                    .others
+                    .iter()
+                    .map(|(name, session)| (name.as_str(), &session.ctx))
+                    .chain([(self.session.as_str(), &self.ctx)])
+                    .collect::<Vec<_>>();
+                sessions.sort_by_key(|&(name, _)| name);
+                for (name, ctx) in sessions {
+                    let current = if name == self.session { '*' } else { ' ' };
+                    let _ = writeln!(out, "{} {:<16} {}", current, name, status(ctx));
+                }
+            }
+            "help" | "h" | "?" => {
+                let names = locale::get().names.join(" ");
+                let _ = writeln!(out, "{}", fill(&messages.help, &[("names", &names)]));
+            }
+            "quit" | "q" | "exit" => return Ok(Flow::Quit),
+            _ => return Err(fill(&messages.unknown_command, &[("command", &command)])),
+        }
+        Ok(Flow::Continue)
+    }
+    /// Make `name` the current session, starting a new vault if there's no such session
+    fn switch(&mut self, name: &str, out: &mut String) {
+        let messages = &locale::get().messages;
+        if name == self.session {
+            let _ = writeln!(
+                out,
+                "{}",
+                fill(&messages.session_already, &[("name", &name)])
+            );
+            return;
+        }
+        let started = !self.others.contains_key(name);
+        let next = self.others.remove(name).unwrap_or_else(|| Session {
+            ctx: SolverContext::new(),
+            pending: None,
+        });
+        let previous = Session {
+            ctx: std::mem::replace(&mut self.ctx, next.ctx),
+            pending: std::mem::replace(&mut self.pending, next.pending),
+        };
+        let previous_name = std::mem::replace(&mut self.session, name.to_string());
+        self.others.insert(previous_name, previous);
+
+        if started {
+            let _ = writeln!(
+                out,
+                "{}",
+                fill(&messages.session_started, &[("name", &name)])
+            );
+            self.suggest(out);
+            return;
+        }
+        let _ = writeln!(
+            out,
+            "{}",
+            fill(
+                &messages.session_status,
+                &[("name", &name), ("status", &status(&self.ctx))]
+            )
+        );
+        if let Some(guess) = self.pending {
+            let _ = writeln!(
+                out,
+                "{}",
+                fill(&messages.playing, &[("guess", &format_guess(guess))])
+            );
+        }
+    }
+    /// Write the history to `path` in the [SolverState] text format, one guess per line
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let text = format!(
+            "# One guess per line: its symbols from 0 to b (? if unknown), then the result\n{}",
+            self.ctx.state().to_text()
+        );
+        std::fs::write(path, text).map_err(|e| {
+            fill(
+                &locale::get().messages.write_failed,
+                &[("file", &path), ("error", &e)],
+            )
+        })
+    }
+    /// Replace the current vault with the one saved in `path`. Results that leave no possible
+    /// secret are still loaded (So they can be undone), but the first one is reported
+    pub fn load(&mut self, path: &str, out: &mut String) -> Result<(), String> {
+        let messages = &locale::get().messages;
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| fill(&messages.read_failed, &[("file", &path), ("error", &e)]))?;
+        let (state, lines) = SolverState::parse_with_lines(&text)?;
+
+        let mut ctx = SolverContext::with_strategy(state.strategy);
+        ctx.set_tie_break(state.tie_break);
+        let mut inconsistent = None;
+        for (entry, line) in state.history.iter().zip(lines) {
+            check_result(entry.correct_positions, entry.correct_symbols)
+                .map_err(|e| fill(&messages.line_error, &[("line", &line), ("error", &e)]))?;
+            ctx.apply_partial_result(entry.guess, entry.correct_positions, entry.correct_symbols);
+            if inconsistent.is_none() && ctx.remaining_count() == 0 {
+                inconsistent = Some((line, *entry));
+            }
+        }
+        self.ctx = ctx;
+        self.pending = None;
+        let _ = writeln!(
+            out,
+            "{}",
+            fill(
+                &messages.loaded,
+                &[("guesses", &self.ctx.history().len()), ("file", &path)]
+            )
+        );
+        if let Some((line, entry)) = inconsistent {
+            let _ = writeln!(
+                out,
+                "{}",
+                fill(
+                    &messages.contradiction,
+                    &[("line", &line), ("entry", &entry)]
+                )
+            );
+        }
+        Ok(())
+    }
+    /// How the candidates would split for each result of the pending guess
+    fn explain(&self, out: &mut String) -> Result<(), String> {
+        let messages = &locale::get().messages;
+        let guess = self
+            .pending
+            .ok_or_else(|| messages.nothing_to_explain.clone())?;
+        let candidates = self.ctx.rank_candidates();
+        let _ = writeln!(
+            out,
+            "{}",
+            fill(
+                &messages.remaining,
+                &[
+                    ("candidates", &candidates.len()),
+                    ("attempts", &self.ctx.attempts_left())
+                ]
+            )
+        );
+        if self.ctx.goes_for_the_win() {
+            let probability = candidates
+                .iter()
+                .find(|candidate| candidate.secret == guess)
+                .map_or(0.0, |candidate| candidate.probability);
+            let _ = writeln!(
+                out,
+                "{}",
+                fill(
+                    &messages.explain_win,
+                    &[
+                        ("guess", &format_guess(guess)),
+                        ("probability", &format!("{:.1}", probability * 100.0))
+                    ]
+                )
+            );
+            return Ok(());
+        }
+        let results = result_split(&self.ctx, guess);
+        let expected =
+            results.iter().map(|&(_, n)| n * n).sum::<usize>() as f64 / candidates.len() as f64;
+        let template = if self.ctx.is_consistent(guess) {
+            &messages.explain_consistent
+        } else {
+            &messages.explain_inconsistent
+        };
+        let _ = writeln!(
+            out,
+            "{}",
+            fill(
+                template,
+                &[
+                    ("guess", &format_guess(guess)),
+                    ("worst", &results.first().map_or(0, |&(_, n)| n)),
+                    ("average", &format!("{:.1}", expected))
+                ]
+            )
+        );
+        for ((p, s), n) in results {
+            let _ = writeln!(
+                out,
+                "{}",
+                fill(
+                    &messages.explain_result,
+                    &[("positions", &p), ("symbols", &s), ("candidates", &n)]
+                )
+            );
+        }
+        Ok(())
+    }
+    /// Run commands from `input` until `quit` or the end of the input
+    pub fn run(&mut self, input: impl BufRead, mut output: impl Write) -> std::io::Result<()> {
+        let mut out = String::new();
+        self.start(&mut out);
+        let prompt = &locale::get().messages.prompt;
+        write!(output, "{}{}", out, prompt)?;
+        output.flush()?;
+        for line in input.lines() {
+            let mut out = String::new();
+            let flow = self.execute(&line?, &mut out);
+            output.write_all(out.as_bytes())?;
+            match flow {
+                Ok(Flow::Quit) => return Ok(()),
+                Ok(Flow::Continue) => {}
+                Err(e) => writeln!(output, "{}", error_message(&e))?,
+            }
+            write!(output, "{}", prompt)?;
+            output.flush()?;
+        }
+        writeln!(output)
+    }
+}
+
+fn solved(ctx: &SolverContext) -> bool {
+    matches!(ctx.history().last(), Some(&(_, p, 0)) if p as usize == ANSWER_SIZE)
+}
+
+/// How far along the vault of `ctx` is, like `3 guesses, 24 candidates`
+fn status(ctx: &SolverContext) -> String {
+    let messages = &locale::get().messages;
+    let guesses = ctx.history().len();
+    if solved(ctx) {
+        fill(&messages.status_solved, &[("guesses", &guesses)])
+    } else if ctx.remaining_count() == 0 {
+        fill(&messages.status_no_candidates, &[("guesses", &guesses)])
+    } else {
+        fill(
+            &messages.status,
+            &[
+                ("guesses", &guesses),
+                ("candidates", &ctx.remaining_count()),
+            ],
+        )
+    }
+}
+
+/// An error as shown to the user
+pub fn error_message(error: &str) -> String {
+    fill(&locale::get().messages.error, &[("error", &error)])
+}
+
+/// Forget the last result applied to `ctx`, returning it. None if there was none
+pub fn undo(ctx: &mut SolverContext) -> Option<(PartialAnswer, u8, u8)> {
+    let mut history = ctx.history().to_vec();
+    let last = history.pop()?;
+    ctx.reset();
+    for &(guess, p, s) in history.iter() {
+        ctx.apply_partial_result(guess, p, s);
+    }
+    Some(last)
+}
+
+/// How many candidates each result of `guess` would leave, biggest first. Results no candidate
+/// gives are left out
+pub fn result_split(ctx: &SolverContext, guess: PossibleAnswer) -> Vec<((usize, usize), usize)> {
+    let mut counts = [[0usize; ANSWER_SIZE + 1]; ANSWER_SIZE + 1];
+    for candidate in ctx.rank_candidates() {
+        let (p, s) = compare(&[candidate.secret, guess]);
+        counts[p as usize][s as usize] += 1;
+    }
+    let mut results = counts
+        .iter()
+        .enumerate()
+        .flat_map(|(p, row)| row.iter().enumerate().map(move |(s, &n)| ((p, s), n)))
+        .filter(|&(_, n)| n > 0)
+        .collect::<Vec<_>>();
+    results.sort_by_key(|&(_, n)| std::cmp::Reverse(n));
+    results
+}
+
+/// Like [format_guess], with `?` for unknown symbols
+pub fn format_partial_guess(guess: PartialAnswer) -> String {
+    guess
+        .iter()
+        .map(|symbol| symbol.map_or("?", |symbol| &locale::get().names[symbol as usize]))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn format_repair(repair: &solver::consistency::Repair) -> String {
+    use solver::consistency::Correction;
+    let messages = &locale::get().messages;
+    repair
+        .corrections
+        .iter()
+        .map(|correction| match *correction {
+            Correction::Symbol {
+                entry,
+                slot,
+                symbol,
+            } => fill(
+                &messages.repair_symbol,
+                &[
+                    ("slot", &(slot + 1)),
+                    ("guess", &(entry + 1)),
+                    ("symbol", &locale::get().names[symbol as usize]),
+                ],
+            ),
+            Correction::Result {
+                entry,
+                correct_positions,
+                correct_symbols,
+            } => fill(
+                &messages.repair_result,
+                &[
+                    ("guess", &(entry + 1)),
+                    ("positions", &correct_positions),
+                    ("symbols", &correct_symbols),
+                ],
+            ),
+        })
+        .collect::<Vec<_>>()
+        .join(&messages.repair_and)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn run(repl: &mut Repl, line: &str) -> Result<Flow, String> {
+        repl.execute(line, &mut String::new())
+    }
+
+    #[test]
+    fn validates_results() {
+        assert_eq!(parse_result("2,1"), Ok((2, 1)));
+        assert_eq!(parse_result(" 0 , 4 "), Ok((0, 4)));
+        assert_eq!(parse_result("4 0"), Ok((4, 0)));
+        assert!(parse_result("3,1").is_err());
+        assert!(parse_result("3,2").is_err());
+        assert!(parse_result("5,0").is_err());
+        assert!(parse_result("2").is_err());
+        assert!(parse_result("a,1").is_err());
+    }
+
+    #[test]
+    fn session() {
+        let secret = [1, 4, 6, 9];
+        let mut repl = Repl::new();
+        repl.start(&mut String::new());
+        assert!(run(&mut repl, "result 5,0").is_err());
+        assert!(run(&mut repl, "frobnicate").is_err());
+        assert_eq!(repl.ctx.history().len(), 0);
+
+        // Play a different first guess than suggested
+        assert_eq!(run(&mut repl, "guess J J J J"), Ok(Flow::Continue));
+        let (p, s) = compare(&[secret, [0, 0, 0, 0]]);
+        run(&mut repl, &format!("result {},{}", p, s)).unwrap();
+        assert_eq!(repl.ctx.history()[0].0, [Some(0); ANSWER_SIZE]);
+
+        for _ in 0..solver::MAX_GUESSES {
+            let guess = repl.pending.unwrap();
+            let (p, s) = compare(&[secret, guess]);
+            run(&mut repl, &format!("r {} {}", p, s)).unwrap();
+            if guess == secret {
+                break;
+            }
+        }
+        assert!(repl.solved());
+        assert!(run(&mut repl, "result 0,0").is_err());
+
+        let len = repl.ctx.history().len();
+        run(&mut repl, "undo").unwrap();
+        assert_eq!(repl.ctx.history().len(), len - 1);
+        assert!(repl.ctx.is_consistent(secret));
+        let mut out = String::new();
+        repl.execute("explain", &mut out).unwrap();
+        assert!(out.contains("candidates"));
+
+        run(&mut repl, "new").unwrap();
+        assert!(repl.ctx.history().is_empty());
+        assert_eq!(run(&mut repl, "quit"), Ok(Flow::Quit));
+    }
+
+    #[test]
+    fn sessions() {
+        let mut repl = Repl::new();
+        repl.start(&mut String::new());
+        run(&mut repl, "result 0,1").unwrap();
+        run(&mut repl, "guess 3 3 4 ><").unwrap();
+
+        let mut out = String::new();
+        repl.execute("session library", &mut out).unwrap();
+        assert!(out.contains("Started session library"));
+        assert!(repl.ctx.history().is_empty());
+        run(&mut repl, "result 4,0").unwrap();
+        assert!(repl.solved());
+
+        // Coming back keeps the history and the guess that was played
+        let mut out = String::new();
+        repl.execute("session main", &mut out).unwrap();
+        assert_eq!(repl.ctx.history().len(), 1);
+        assert_eq!(repl.pending, Some([3, 3, 4, 4]));
+        assert!(out.contains("Session main: 1 guesses, "));
+
+        let mut out = String::new();
+        repl.execute("sessions", &mut out).unwrap();
+        let lines = out.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with("  library          solved in 1 guesses"));
+        assert!(lines[1].starts_with("* main             1 guesses, "));
+
+        assert!(run(&mut repl, "session").is_err());
+        assert!(run(&mut repl, "session two words").is_err());
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path =
+            std::env::temp_dir().join(format!("nickracker-console-{}.txt", std::process::id()));
+        let path = path.to_str().unwrap();
+        let mut repl = Repl::new();
+        repl.start(&mut String::new());
+        run(&mut repl, "result 0,1").unwrap();
+        run(&mut repl, "guess 3 3 4 ><").unwrap();
+        run(&mut repl, "result 1,0").unwrap();
+        run(&mut repl, &format!("save {}", path)).unwrap();
+
+        let mut loaded = Repl::new();
+        run(&mut loaded, &format!("load {}", path)).unwrap();
+        assert_eq!(loaded.ctx.history(), repl.ctx.history());
+        assert_eq!(loaded.pending, repl.pending);
+
+        // A result that contradicts the others is reported with its line
+        let text = std::fs::read_to_string(path).unwrap() + "0012:4,0\n";
+        std::fs::write(path, text).unwrap();
+        let mut out = String::new();
+        loaded.execute(&format!("load {}", path), &mut out).unwrap();
+        assert!(out.contains("Line 7 (0012:4,0) contradicts"));
+        assert_eq!(loaded.ctx.remaining_count(), 0);
+
+        std::fs::write(path, "nickracker-state 3\n0012:3,1\n").unwrap();
+        assert_eq!(
+            run(&mut loaded, &format!("load {}", path)),
+            Err("Line 2: 3,1 is impossible: if 3 symbols are in place the last one can't be elsewhere".to_string())
+        );
+        assert_eq!(loaded.ctx.history().len(), 3);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn stops_at_end_of_input() {
+        let mut output = Vec::new();
+        Repl::new()
+            .run("result 0,1\nbad\ncandidates 2\n".as_bytes(), &mut output)
+            .unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("Guess 2: "));
+        assert!(output.contains("Error: Unknown command 'bad'"));
+    }
+}
@@ -0,0 +1,106 @@
+//! Names of the vault symbols, and parsing guesses typed by the user.
+//!
+//! The names in use come from the [crate::locale], these are the built-in ones.
+use solver::{PossibleAnswer, ANSWER_SIZE, SYMBOL_COUNT};
+
+use crate::locale::{self, fill};
+
+pub const SYMBOL_IDX_TO_LETTER: [&str; SYMBOL_COUNT] = [
+    "Inverted J? The first symbol",
+    "B",
+    "C",
+    "F",
+    "> and < on top of each other (NOT the X, they cross!)",
+    "M",
+    "P",
+    "The thing between P and S under B",
+    "S (Jagged, like a thunder)",
+    "T (Like a cross, the upper line has an angle downwards)",
+    "|X| (Between the T and X)",
+    "X",
+];
+
+/// Short names for each symbol, for places where the long descriptions above don't fit
+pub const SYMBOL_SHORT_NAMES: [&str; SYMBOL_COUNT] = [
+    "J",
+    "B",
+    "C",
+    "F",
+    "><",
+    "M",
+    "P",
+    "Hourglass",
+    "S",
+    "T",
+    "|X|",
+    "X",
+];
+
+/// A symbol by its index (0 to 11) or name, ignoring case
+pub fn parse_symbol(text: &str) -> Option<u8> {
+    if let Ok(idx) = text.parse::<u8>() {
+        return ((idx as usize) < SYMBOL_COUNT).then_some(idx);
+    }
+    locale::get()
+        .names
+        .iter()
+        .position(|name| name.eq_ignore_ascii_case(text))
+        .map(|idx| idx as u8)
+}
+
+/// A guess as 4 symbols separated by spaces or commas, like `J B >< 11`
+pub fn parse_guess(text: &str) -> Result<PossibleAnswer, String> {
+    let parts = text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>();
+    let messages = &locale::get().messages;
+    if parts.len() != ANSWER_SIZE {
+        return Err(fill(
+            &messages.symbol_count,
+            &[("expected", &ANSWER_SIZE), ("got", &parts.len())],
+        ));
+    }
+    let mut guess = [0; ANSWER_SIZE];
+    for (symbol, part) in guess.iter_mut().zip(parts) {
+        *symbol = parse_symbol(part).ok_or_else(|| {
+            fill(
+                &messages.unknown_symbol,
+                &[("symbol", &part), ("names", &locale::get().names.join(" "))],
+            )
+        })?;
+    }
+    Ok(guess)
+}
+
+/// A code like [parse_guess] takes, or written as 4 base 12 digits like `0a1b` (The format of
+/// [solver::state])
+pub fn parse_code(text: &str) -> Result<PossibleAnswer, String> {
+    solver::state::parse_answer(text).map_or_else(|| parse_guess(text), Ok)
+}
+
+/// Names of the symbols of a guess, like `J J B C`
+pub fn format_guess(guess: PossibleAnswer) -> String {
+    guess
+        .iter()
+        .map(|&symbol| locale::get().names[symbol as usize].as_str())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parses_guesses() {
+        assert_eq!(parse_guess("J b >< 11"), Ok([0, 1, 4, 11]));
+        assert_eq!(parse_guess("hourglass,|x|, 3 ,x"), Ok([7, 10, 3, 11]));
+        assert_eq!(parse_guess(&format_guess([5, 6, 8, 9])), Ok([5, 6, 8, 9]));
+        assert!(parse_guess("J B C").is_err());
+        assert!(parse_guess("J B C 12").is_err());
+        assert!(parse_guess("J B C Q").is_err());
+        assert_eq!(parse_code("0a1b"), Ok([0, 10, 1, 11]));
+        assert_eq!(parse_code("0 10 1 X"), Ok([0, 10, 1, 11]));
+    }
+}
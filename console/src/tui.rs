@@ -0,0 +1,322 @@
+//! `console --tui`: a full screen terminal UI around the [crate::repl] commands (Built with the
+//! `tui` feature).
+//!
+//! It shows the board of guesses with their results and the next suggestion, a heatmap of how
+//! likely each symbol is in each slot, and once few enough are left a scrollable list of the
+//! candidates. Commands are typed in the input line at the bottom, like in the REPL. Up/Down and
+//! PageUp/PageDown scroll the candidates, Esc quits.
+use std::fmt::Write as _;
+
+use ratatui::{
+    backend::Backend,
+    crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Paragraph, Wrap},
+    Frame, Terminal,
+};
+use solver::{ANSWER_SIZE, MAX_GUESSES, SYMBOL_COUNT};
+
+use crate::locale::{self, fill};
+use crate::repl::{error_message, format_partial_guess, Flow, Repl};
+use crate::symbols::*;
+
+/// The candidate list is only shown once at most this many candidates are left
+const LISTED_CANDIDATES: usize = 500;
+
+/// Width of the symbol names column of the heatmap
+fn name_width() -> usize {
+    let longest = locale::get().names.iter().map(|name| name.chars().count());
+    longest.max().unwrap_or(0) + 1
+}
+
+struct App {
+    repl: Repl,
+    input: String,
+    /// What the last command printed
+    log: String,
+    /// First candidate shown in the list
+    scroll: usize,
+    quit: bool,
+}
+
+/// Background for a cell of the heatmap, hotter the more likely
+fn heat_style(probability: f64) -> Style {
+    let color = match probability {
+        p if p <= 0.0 => return Style::new().fg(Color::DarkGray),
+        p if p < 0.1 => Color::Blue,
+        p if p < 0.3 => Color::Cyan,
+        p if p < 0.6 => Color::Yellow,
+        _ => Color::Red,
+    };
+    Style::new().bg(color).fg(Color::Black)
+}
+
+impl App {
+    fn new(mut repl: Repl) -> Self {
+        let mut log = String::new();
+        repl.start(&mut log);
+        Self {
+            repl,
+            input: String::new(),
+            log,
+            scroll: 0,
+            quit: false,
+        }
+    }
+    /// Run the command in the input line
+    fn submit(&mut self) {
+        let line = std::mem::take(&mut self.input);
+        let mut out = String::new();
+        match self.repl.execute(&line, &mut out) {
+            Ok(Flow::Quit) => self.quit = true,
+            Ok(Flow::Continue) => {}
+            Err(e) => {
+                let _ = writeln!(out, "{}", error_message(&e));
+            }
+        }
+        self.log = out;
+        self.scroll = 0;
+    }
+    fn scroll_by(&mut self, delta: isize) {
+        let last = self.repl.ctx().remaining_count().saturating_sub(1);
+        self.scroll = self.scroll.saturating_add_signed(delta).min(last);
+    }
+    fn key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => self.quit = true,
+            KeyCode::Char(c) => self.input.push(c),
+            KeyCode::Backspace => {
+                self.input.pop();
+            }
+            KeyCode::Enter => self.submit(),
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Up => self.scroll_by(-1),
+            KeyCode::Down => self.scroll_by(1),
+            KeyCode::PageUp => self.scroll_by(-10),
+            KeyCode::PageDown => self.scroll_by(10),
+            _ => {}
+        }
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, log, input] = Layout::vertical([
+            // The board with every guess used and its status, or the heatmap, with the borders
+            Constraint::Length((MAX_GUESSES + 5).max(SYMBOL_COUNT + 3) as u16),
+            // Tall enough for the art of the suggestion
+            Constraint::Min(16),
+            Constraint::Length(3),
+        ])
+        .areas(frame.area());
+        let [board, heatmap, candidates] = Layout::horizontal([
+            Constraint::Length(36),
+            // Names, a cell per slot and the borders
+            Constraint::Length((name_width() + 5 * ANSWER_SIZE + 2) as u16),
+            Constraint::Min(20),
+        ])
+        .areas(main);
+        self.draw_board(frame, board);
+        self.draw_heatmap(frame, heatmap);
+        self.draw_candidates(frame, candidates);
+
+        let messages = &locale::get().messages;
+        let log_text = self.log.replace('\t', "    ");
+        frame.render_widget(
+            Paragraph::new(log_text)
+                .wrap(Wrap { trim: false })
+                .block(Block::bordered().title(messages.tui_output.as_str())),
+            log,
+        );
+        frame.render_widget(
+            Paragraph::new(format!("> {}", self.input))
+                .block(Block::bordered().title(messages.tui_command.as_str())),
+            input,
+        );
+        frame.set_cursor_position((input.x + 3 + self.input.chars().count() as u16, input.y + 1));
+    }
+    fn draw_board(&self, frame: &mut Frame, area: Rect) {
+        let ctx = self.repl.ctx();
+        let mut lines = ctx
+            .history()
+            .iter()
+            .enumerate()
+            .map(|(i, &(guess, p, s))| {
+                Line::from(format!(
+                    "{:>2}. {:<24} {},{}",
+                    i + 1,
+                    format_partial_guess(guess),
+                    p,
+                    s
+                ))
+            })
+            .collect::<Vec<_>>();
+        if let Some(guess) = self.repl.pending() {
+            lines.push(
+                Line::from(format!(
+                    "{:>2}. {:<24} ?",
+                    ctx.history().len() + 1,
+                    format_guess(guess)
+                ))
+                .style(Style::new().add_modifier(Modifier::BOLD | Modifier::REVERSED)),
+            );
+        }
+        lines.push(Line::from(""));
+        let messages = &locale::get().messages;
+        lines.push(Line::from(fill(
+            &messages.remaining,
+            &[
+                ("candidates", &ctx.remaining_count()),
+                ("attempts", &ctx.attempts_left()),
+            ],
+        )));
+        let title = fill(&messages.tui_board, &[("name", &self.repl.session())]);
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title(title)),
+            area,
+        );
+    }
+    fn draw_heatmap(&self, frame: &mut Frame, area: Rect) {
+        let probabilities = self.repl.ctx().symbol_probabilities();
+        let name_width = name_width();
+        let mut lines = vec![Line::from(format!(
+            "{:name_width$}{}",
+            "",
+            (1..=ANSWER_SIZE)
+                .map(|slot| format!("{:>5}", slot))
+                .collect::<String>()
+        ))];
+        for (symbol, name) in locale::get().names.iter().enumerate() {
+            let mut spans = vec![Span::raw(format!("{:name_width$}", name))];
+            for slot in probabilities.iter() {
+                let probability = slot[symbol];
+                spans.push(Span::raw(" "));
+                spans.push(Span::styled(
+                    format!("{:>3.0}%", probability * 100.0),
+                    heat_style(probability),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+        frame.render_widget(
+            Paragraph::new(lines)
+                .block(Block::bordered().title(locale::get().messages.tui_heatmap.as_str())),
+            area,
+        );
+    }
+    fn draw_candidates(&self, frame: &mut Frame, area: Rect) {
+        let ctx = self.repl.ctx();
+        let count = ctx.remaining_count();
+        let messages = &locale::get().messages;
+        let block =
+            Block::bordered().title(fill(&messages.tui_candidates, &[("candidates", &count)]));
+        let paragraph = if count == 0 {
+            Paragraph::new(messages.tui_no_candidates.as_str())
+        } else if count > LISTED_CANDIDATES {
+            Paragraph::new(fill(
+                &messages.tui_too_many,
+                &[("limit", &LISTED_CANDIDATES)],
+            ))
+        } else {
+            let lines = ctx
+                .rank_candidates()
+                .iter()
+                .skip(self.scroll)
+                .map(|candidate| {
+                    Line::from(format!(
+                        "{:<24} {:>6.2}%",
+                        format_guess(candidate.secret),
+                        candidate.probability * 100.0
+                    ))
+                })
+                .collect::<Vec<_>>();
+            Paragraph::new(lines)
+        };
+        frame.render_widget(paragraph.wrap(Wrap { trim: false }).block(block), area);
+    }
+}
+
+fn event_loop(terminal: &mut Terminal<impl Backend>, app: &mut App) -> std::io::Result<()> {
+    while !app.quit {
+        terminal.draw(|frame| app.draw(frame))?;
+        if let Event::Key(key) = event::read()? {
+            // Windows also reports releases
+            if key.kind == KeyEventKind::Press {
+                app.key(key);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Take over the terminal until the user quits
+pub fn run(repl: Repl) -> std::io::Result<()> {
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut App::new(repl));
+    ratatui::restore();
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ratatui::backend::TestBackend;
+
+    fn screen(app: &App) -> String {
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        terminal.draw(|frame| app.draw(frame)).unwrap();
+        let buffer = terminal.backend().buffer();
+        buffer
+            .content()
+            .chunks(buffer.area.width as usize)
+            .map(|row| row.iter().map(|cell| cell.symbol()).collect::<String>() + "\n")
+            .collect()
+    }
+
+    fn type_line(app: &mut App, line: &str) {
+        for c in line.chars() {
+            app.key(KeyEvent::from(KeyCode::Char(c)));
+        }
+        app.key(KeyEvent::from(KeyCode::Enter));
+    }
+
+    #[test]
+    fn shows_the_vault() {
+        let mut app = App::new(Repl::new());
+        let start = screen(&app);
+        assert!(start.contains(" 1. J J B C"));
+        assert!(start.contains("Candidates (20736)"));
+        assert!(start.contains("Listed once at most"));
+
+        let secret = [1, 4, 6, 9];
+        let play = |app: &mut App| {
+            let guess = app.repl.pending().unwrap();
+            let (p, s) = solver::compare(&[secret, guess]);
+            type_line(app, &format!("result {},{}", p, s));
+        };
+        while app.repl.ctx().remaining_count() > LISTED_CANDIDATES {
+            play(&mut app);
+        }
+        let ranked = app.repl.ctx().rank_candidates();
+        assert!(screen(&app).contains(&format_guess(ranked[0].secret)));
+        app.scroll_by(ranked.len() as isize);
+        assert_eq!(app.scroll, ranked.len() - 1);
+        let last = ranked.last().unwrap().secret;
+        assert!(screen(&app).contains(&format_guess(last)));
+        while app.repl.pending().is_some() {
+            play(&mut app);
+        }
+        // Solved, so B is certainly in the first slot and nowhere else
+        assert!(screen(&app).contains(&format!(
+            "{:w$} 100%{}",
+            "B",
+            "   0%".repeat(3),
+            w = name_width()
+        )));
+
+        type_line(&mut app, "frobnicate");
+        assert!(screen(&app).contains("Error: Unknown command"));
+        type_line(&mut app, "quit");
+        assert!(app.quit);
+    }
+}
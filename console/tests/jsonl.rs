@@ -0,0 +1,46 @@
+//! Drives `console --jsonl` as a separate process with the scripted transcript in
+//! `jsonl_transcript.txt`.
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+use serde_json::Value;
+
+#[test]
+fn transcript() {
+    let transcript = include_str!("jsonl_transcript.txt");
+    let mut requests = Vec::new();
+    let mut expected = Vec::new();
+    for line in transcript.lines() {
+        if let Some(request) = line.strip_prefix("> ") {
+            requests.push(request);
+        } else if let Some(response) = line.strip_prefix("< ") {
+            expected.push(serde_json::from_str::<Value>(response).unwrap());
+        }
+    }
+    assert_eq!(requests.len(), expected.len());
+
+    let mut console = Command::new(env!("CARGO_BIN_EXE_console"))
+        .arg("--jsonl")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = console.stdin.take().unwrap();
+    let mut stdout = BufReader::new(console.stdout.take().unwrap()).lines();
+    // One request at a time, so a missing response fails here instead of hanging
+    for (request, expected) in requests.iter().zip(expected) {
+        writeln!(stdin, "{}", request).unwrap();
+        stdin.flush().unwrap();
+        let response = stdout.next().unwrap().unwrap();
+        let mut response = serde_json::from_str::<Value>(&response).unwrap();
+        // Error messages can be reworded, clients should go by the code
+        if response["ok"] == false {
+            let error = response.as_object_mut().unwrap().remove("error");
+            assert!(error.unwrap().as_str().is_some_and(|e| !e.is_empty()));
+        }
+        assert_eq!(response, expected, "Response to {}", request);
+    }
+    drop(stdin);
+    assert!(stdout.next().is_none());
+    assert!(console.wait().unwrap().success());
+}
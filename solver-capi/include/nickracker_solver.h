@@ -0,0 +1,108 @@
+#ifndef NICKRACKER_SOLVER_H
+#define NICKRACKER_SOLVER_H
+
+/* Generated with cbindgen from solver-capi/src/lib.rs. Don't edit by hand */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Bump this whenever a function signature or the meaning of a status code changes
+ */
+#define NICKRACKER_ABI_VERSION 1
+
+#define NICKRACKER_OK 0
+
+/**
+ * A pointer argument was null
+ */
+#define NICKRACKER_NULL_POINTER -1
+
+/**
+ * A symbol was out of range, or the vault can't give that result
+ */
+#define NICKRACKER_INVALID_ARGUMENT -2
+
+/**
+ * No secret is consistent with the applied results, so there is nothing to guess
+ */
+#define NICKRACKER_NO_CANDIDATES -3
+
+/**
+ * The solver panicked. Shouldn't happen, please report it
+ */
+#define NICKRACKER_INTERNAL_ERROR -4
+
+/**
+ * Opaque handle to a solver context
+ */
+typedef struct NickrackerSolver NickrackerSolver;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+/**
+ * Version of the API the library implements, see [NICKRACKER_ABI_VERSION]
+ */
+uint32_t nickracker_abi_version(void);
+
+/**
+ * Create a solver context for a new vault. Free it with [nickracker_solver_free]. Null if it
+ * couldn't be created
+ */
+NickrackerSolver *nickracker_solver_new(void);
+
+/**
+ * Free a solver context. Null is ignored
+ *
+ * # Safety
+ * `solver` must be null or a pointer returned by [nickracker_solver_new] that wasn't freed yet
+ */
+void nickracker_solver_free(NickrackerSolver *solver);
+
+/**
+ * Start a new vault, forgetting all applied results
+ *
+ * # Safety
+ * `solver` must be null or a valid pointer returned by [nickracker_solver_new]
+ */
+int32_t nickracker_solver_reset(NickrackerSolver *solver);
+
+/**
+ * Tell the solver the result the vault gave for `guess` (An array of 4 symbols)
+ *
+ * # Safety
+ * `solver` must be null or a valid pointer returned by [nickracker_solver_new], and `guess` must
+ * be null or point to 4 readable bytes
+ */
+int32_t nickracker_solver_apply_result(NickrackerSolver *solver,
+                                       const uint8_t *guess,
+                                       uint8_t correct_positions,
+                                       uint8_t correct_symbols);
+
+/**
+ * Write the next guess (4 symbols) to `out_guess`
+ *
+ * # Safety
+ * `solver` must be null or a valid pointer returned by [nickracker_solver_new], and `out_guess`
+ * must be null or point to 4 writable bytes
+ */
+int32_t nickracker_solver_guess(NickrackerSolver *solver, uint8_t *out_guess);
+
+/**
+ * Number of secrets still consistent with the applied results. 0 if `solver` is null
+ *
+ * # Safety
+ * `solver` must be null or a valid pointer returned by [nickracker_solver_new]
+ */
+size_t nickracker_solver_remaining(const NickrackerSolver *solver);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* NICKRACKER_SOLVER_H */
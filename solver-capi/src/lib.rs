@@ -0,0 +1,258 @@
+//! C API for the solver, for tools that can't use the `solver` crate directly (C, C++, C# through
+//! P/Invoke...).
+//!
+//! Builds `libnickracker_solver.so` (Or `nickracker_solver.dll`) and a static library. The
+//! matching header is in `include/nickracker_solver.h`, and `tests/c/test.c` shows how to use it.
+//! Symbols are numbered 0 to 11 like in the solver, and every function that can fail returns one
+//! of the `NICKRACKER_*` status codes. Panics are caught before they reach the caller, since
+//! unwinding into C is undefined behaviour.
+use solver::{PossibleAnswer, SolverContext, ANSWER_SIZE, SYMBOL_COUNT};
+
+/// Bump this whenever a function signature or the meaning of a status code changes
+pub const NICKRACKER_ABI_VERSION: u32 = 1;
+
+pub const NICKRACKER_OK: i32 = 0;
+/// A pointer argument was null
+pub const NICKRACKER_NULL_POINTER: i32 = -1;
+/// A symbol was out of range, or the vault can't give that result
+pub const NICKRACKER_INVALID_ARGUMENT: i32 = -2;
+/// No secret is consistent with the applied results, so there is nothing to guess
+pub const NICKRACKER_NO_CANDIDATES: i32 = -3;
+/// The solver panicked. Shouldn't happen, please report it
+pub const NICKRACKER_INTERNAL_ERROR: i32 = -4;
+
+/// Opaque handle to a solver context
+pub struct NickrackerSolver {
+    ctx: SolverContext,
+}
+
+/// Run `f`, returning `on_panic` if it panics
+fn catch_panic<T>(on_panic: T, f: impl FnOnce() -> T) -> T {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).unwrap_or(on_panic)
+}
+
+/// Whether the vault can give this result. The same rules as the console's `result` command
+fn is_possible_result(correct_positions: u8, correct_symbols: u8) -> bool {
+    let sum = correct_positions as usize + correct_symbols as usize;
+    // With all but one symbol in place, the last one can't be elsewhere
+    sum <= ANSWER_SIZE && !(correct_positions as usize == ANSWER_SIZE - 1 && correct_symbols == 1)
+}
+
+/// Version of the API the library implements, see [NICKRACKER_ABI_VERSION]
+#[no_mangle]
+pub extern "C" fn nickracker_abi_version() -> u32 {
+    NICKRACKER_ABI_VERSION
+}
+
+/// Create a solver context for a new vault. Free it with [nickracker_solver_free]. Null if it
+/// couldn't be created
+#[no_mangle]
+pub extern "C" fn nickracker_solver_new() -> *mut NickrackerSolver {
+    catch_panic(std::ptr::null_mut(), || {
+        Box::into_raw(Box::new(NickrackerSolver {
+            ctx: SolverContext::new(),
+        }))
+    })
+}
+
+/// Free a solver context. Null is ignored
+///
+/// # Safety
+/// `solver` must be null or a pointer returned by [nickracker_solver_new] that wasn't freed yet
+#[no_mangle]
+pub unsafe extern "C" fn nickracker_solver_free(solver: *mut NickrackerSolver) {
+    if !solver.is_null() {
+        catch_panic((), || drop(Box::from_raw(solver)));
+    }
+}
+
+/// Start a new vault, forgetting all applied results
+///
+/// # Safety
+/// `solver` must be null or a valid pointer returned by [nickracker_solver_new]
+#[no_mangle]
+pub unsafe extern "C" fn nickracker_solver_reset(solver: *mut NickrackerSolver) -> i32 {
+    match solver.as_mut() {
+        Some(solver) => catch_panic(NICKRACKER_INTERNAL_ERROR, || {
+            solver.ctx.reset();
+            NICKRACKER_OK
+        }),
+        None => NICKRACKER_NULL_POINTER,
+    }
+}
+
+/// Tell the solver the result the vault gave for `guess` (An array of 4 symbols)
+///
+/// # Safety
+/// `solver` must be null or a valid pointer returned by [nickracker_solver_new], and `guess` must
+/// be null or point to 4 readable bytes
+#[no_mangle]
+pub unsafe extern "C" fn nickracker_solver_apply_result(
+    solver: *mut NickrackerSolver,
+    guess: *const u8,
+    correct_positions: u8,
+    correct_symbols: u8,
+) -> i32 {
+    let solver = match solver.as_mut() {
+        Some(solver) => solver,
+        None => return NICKRACKER_NULL_POINTER,
+    };
+    if guess.is_null() {
+        return NICKRACKER_NULL_POINTER;
+    }
+    let mut answer: PossibleAnswer = [0; ANSWER_SIZE];
+    answer.copy_from_slice(std::slice::from_raw_parts(guess, ANSWER_SIZE));
+    if answer.iter().any(|&symbol| symbol as usize >= SYMBOL_COUNT)
+        || !is_possible_result(correct_positions, correct_symbols)
+    {
+        return NICKRACKER_INVALID_ARGUMENT;
+    }
+    catch_panic(NICKRACKER_INTERNAL_ERROR, || {
+        solver
+            .ctx
+            .apply_result(answer, correct_positions, correct_symbols);
+        NICKRACKER_OK
+    })
+}
+
+/// Write the next guess (4 symbols) to `out_guess`
+///
+/// # Safety
+/// `solver` must be null or a valid pointer returned by [nickracker_solver_new], and `out_guess`
+/// must be null or point to 4 writable bytes
+#[no_mangle]
+pub unsafe extern "C" fn nickracker_solver_guess(
+    solver: *mut NickrackerSolver,
+    out_guess: *mut u8,
+) -> i32 {
+    let solver = match solver.as_mut() {
+        Some(solver) => solver,
+        None => return NICKRACKER_NULL_POINTER,
+    };
+    if out_guess.is_null() {
+        return NICKRACKER_NULL_POINTER;
+    }
+    if solver.ctx.remaining_count() == 0 {
+        return NICKRACKER_NO_CANDIDATES;
+    }
+    catch_panic(NICKRACKER_INTERNAL_ERROR, || {
+        let guess = solver.ctx.guess();
+        std::slice::from_raw_parts_mut(out_guess, ANSWER_SIZE).copy_from_slice(&guess);
+        NICKRACKER_OK
+    })
+}
+
+/// Number of secrets still consistent with the applied results. 0 if `solver` is null
+///
+/// # Safety
+/// `solver` must be null or a valid pointer returned by [nickracker_solver_new]
+#[no_mangle]
+pub unsafe extern "C" fn nickracker_solver_remaining(solver: *const NickrackerSolver) -> usize {
+    solver
+        .as_ref()
+        .map_or(0, |solver| catch_panic(0, || solver.ctx.remaining_count()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn solves_through_the_c_api() {
+        let secret = [7, 2, 9, 2];
+        unsafe {
+            let solver = nickracker_solver_new();
+            assert_eq!(
+                nickracker_solver_remaining(solver),
+                solver::POSSIBLE_ANSWERS
+            );
+            let mut guess = [0u8; ANSWER_SIZE];
+            let mut solved = false;
+            for _ in 0..12 {
+                assert_eq!(
+                    nickracker_solver_guess(solver, guess.as_mut_ptr()),
+                    NICKRACKER_OK
+                );
+                if guess == secret {
+                    solved = true;
+                    break;
+                }
+                let (p, s) = solver::compare(&[secret, guess]);
+                assert_eq!(
+                    nickracker_solver_apply_result(solver, guess.as_ptr(), p, s),
+                    NICKRACKER_OK
+                );
+            }
+            assert!(solved);
+
+            assert_eq!(nickracker_solver_reset(solver), NICKRACKER_OK);
+            assert_eq!(
+                nickracker_solver_remaining(solver),
+                solver::POSSIBLE_ANSWERS
+            );
+            nickracker_solver_free(solver);
+        }
+    }
+
+    #[test]
+    fn rejects_bad_arguments() {
+        unsafe {
+            let solver = nickracker_solver_new();
+            let bad_symbol = [0u8, 0, 1, 12];
+            assert_eq!(
+                nickracker_solver_apply_result(solver, bad_symbol.as_ptr(), 0, 0),
+                NICKRACKER_INVALID_ARGUMENT
+            );
+            let guess = [0u8, 0, 1, 2];
+            assert_eq!(
+                nickracker_solver_apply_result(solver, guess.as_ptr(), 3, 2),
+                NICKRACKER_INVALID_ARGUMENT
+            );
+            // The last symbol can't be elsewhere when the other 3 are in place
+            assert_eq!(
+                nickracker_solver_apply_result(solver, guess.as_ptr(), 3, 1),
+                NICKRACKER_INVALID_ARGUMENT
+            );
+            assert_eq!(
+                nickracker_solver_apply_result(solver, std::ptr::null(), 0, 0),
+                NICKRACKER_NULL_POINTER
+            );
+            assert_eq!(
+                nickracker_solver_guess(std::ptr::null_mut(), std::ptr::null_mut()),
+                NICKRACKER_NULL_POINTER
+            );
+
+            // Contradicting results leave nothing to guess
+            nickracker_solver_apply_result(solver, guess.as_ptr(), 4, 0);
+            nickracker_solver_apply_result(solver, guess.as_ptr(), 0, 0);
+            let mut out = [0u8; ANSWER_SIZE];
+            assert_eq!(
+                nickracker_solver_guess(solver, out.as_mut_ptr()),
+                NICKRACKER_NO_CANDIDATES
+            );
+            nickracker_solver_free(solver);
+            nickracker_solver_free(std::ptr::null_mut());
+        }
+    }
+
+    #[test]
+    fn many_results() {
+        unsafe {
+            let solver = nickracker_solver_new();
+            let guess = [0u8, 0, 1, 2];
+            for _ in 0..300 {
+                assert_eq!(
+                    nickracker_solver_apply_result(solver, guess.as_ptr(), 1, 0),
+                    NICKRACKER_OK
+                );
+            }
+            assert!(nickracker_solver_remaining(solver) > 0);
+            let mut out = [0u8; ANSWER_SIZE];
+            assert_eq!(
+                nickracker_solver_guess(solver, out.as_mut_ptr()),
+                NICKRACKER_OK
+            );
+            nickracker_solver_free(solver);
+        }
+    }
+}
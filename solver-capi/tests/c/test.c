@@ -0,0 +1,84 @@
+/* Solves every secret in a small sample through the C API. Run it with `make test` */
+#include <stdio.h>
+#include <string.h>
+
+#include "nickracker_solver.h"
+
+#define MAX_GUESSES 12
+
+static void compare(const uint8_t secret[4], const uint8_t guess[4], uint8_t *correct_positions,
+                    uint8_t *correct_symbols) {
+    int secret_counts[12] = {0};
+    int guess_counts[12] = {0};
+    *correct_positions = 0;
+    *correct_symbols = 0;
+    for (int i = 0; i < 4; i++) {
+        if (secret[i] == guess[i]) {
+            (*correct_positions)++;
+        } else {
+            secret_counts[secret[i]]++;
+            guess_counts[guess[i]]++;
+        }
+    }
+    for (int symbol = 0; symbol < 12; symbol++) {
+        *correct_symbols += secret_counts[symbol] < guess_counts[symbol] ? secret_counts[symbol]
+                                                                         : guess_counts[symbol];
+    }
+}
+
+static int solve(NickrackerSolver *solver, const uint8_t secret[4]) {
+    if (nickracker_solver_reset(solver) != NICKRACKER_OK) {
+        return -1;
+    }
+    for (int attempt = 1; attempt <= MAX_GUESSES; attempt++) {
+        uint8_t guess[4];
+        if (nickracker_solver_guess(solver, guess) != NICKRACKER_OK) {
+            return -1;
+        }
+        if (memcmp(guess, secret, 4) == 0) {
+            return attempt;
+        }
+        uint8_t correct_positions, correct_symbols;
+        compare(secret, guess, &correct_positions, &correct_symbols);
+        if (nickracker_solver_apply_result(solver, guess, correct_positions, correct_symbols) !=
+            NICKRACKER_OK) {
+            return -1;
+        }
+    }
+    return -1;
+}
+
+int main(void) {
+    if (nickracker_abi_version() != NICKRACKER_ABI_VERSION) {
+        fprintf(stderr, "Header and library ABI versions don't match\n");
+        return 1;
+    }
+    NickrackerSolver *solver = nickracker_solver_new();
+    if (solver == NULL || nickracker_solver_remaining(solver) != 12 * 12 * 12 * 12) {
+        fprintf(stderr, "Bad new solver\n");
+        return 1;
+    }
+
+    const uint8_t bad_guess[4] = {0, 0, 1, 12};
+    if (nickracker_solver_apply_result(solver, bad_guess, 0, 0) != NICKRACKER_INVALID_ARGUMENT ||
+        nickracker_solver_guess(NULL, NULL) != NICKRACKER_NULL_POINTER) {
+        fprintf(stderr, "Bad arguments were not rejected\n");
+        return 1;
+    }
+
+    int solved = 0;
+    for (int i = 0; i < 12 * 12 * 12 * 12; i += 997) {
+        const uint8_t secret[4] = {i % 12, i / 12 % 12, i / 144 % 12, i / 1728 % 12};
+        int attempts = solve(solver, secret);
+        if (attempts < 0) {
+            fprintf(stderr, "Failed to solve %d %d %d %d\n", secret[0], secret[1], secret[2],
+                    secret[3]);
+            nickracker_solver_free(solver);
+            return 1;
+        }
+        solved++;
+    }
+    nickracker_solver_free(solver);
+    printf("Solved %d secrets through the C API\n", solved);
+    return 0;
+}
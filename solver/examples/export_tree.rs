@@ -0,0 +1,17 @@
+//! Print the decision tree of the default strategy from the start of a vault.
+//!
+//! Usage: `cargo run --release --example export_tree -- <dot|json> [depth]`
+use solver::{tree::TreeNode, SolverContext};
+
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let format = args.next().unwrap_or_else(|| "dot".to_string());
+    let depth = args.next().and_then(|x| x.parse().ok()).unwrap_or(3);
+
+    let tree = TreeNode::build(&SolverContext::new(), depth);
+    match format.as_str() {
+        "dot" => print!("{}", tree.to_dot()),
+        "json" => println!("{}", tree.to_json()),
+        _ => eprintln!("Unknown format {}, expected dot or json", format),
+    }
+}
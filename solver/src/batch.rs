@@ -0,0 +1,200 @@
+//! Batched version of [crate::compare], for comparing one guess against many possible answers.
+//!
+//! Almost all of the time spent making a guess goes into comparing every candidate guess with
+//! every remaining possible answer, so this is worth making fast. The answers are stored in
+//! blocks of 16, slot by slot and with their per-symbol counts, so a guess is compared with a
+//! whole block at once: one lane-wise equality per slot gives the correct positions, and one
+//! lane-wise min per distinct symbol of the guess gives how many symbols they share. With the
+//! `simd` feature (Which needs a nightly compiler) that is done explicitly with portable SIMD,
+//! otherwise we use plain loops over the lanes that the compiler can usually vectorize on its own.
+use crate::{PossibleAnswer, ANSWER_SIZE, SYMBOL_COUNT};
+
+use alloc::vec::Vec;
+
+/// How many answers got each result: `histogram[correct_positions][correct_symbols]`
+pub type ResultHistogram = [[u32; ANSWER_SIZE + 1]; ANSWER_SIZE + 1];
+
+/// Answers per block
+const LANES: usize = 16;
+
+/// One value per answer of a block
+type Lanes = [u8; LANES];
+
+/// Symbol for the padding at the end of the last block. Matches no guess symbol, and appears in
+/// no count
+const PADDING: u8 = SYMBOL_COUNT as u8;
+
+/// [LANES] answers, stored slot by slot
+struct Block {
+    /// `symbols[slot][lane]` is the symbol in `slot` of the answer in `lane`
+    symbols: [Lanes; ANSWER_SIZE],
+    /// `counts[symbol][lane]` is how many times `symbol` appears in the answer in `lane`
+    counts: [Lanes; SYMBOL_COUNT],
+}
+
+impl Block {
+    fn new(answers: &[PossibleAnswer]) -> Self {
+        let mut block = Block {
+            symbols: [[PADDING; LANES]; ANSWER_SIZE],
+            counts: [[0; LANES]; SYMBOL_COUNT],
+        };
+        for (lane, answer) in answers.iter().enumerate() {
+            for (slot, &symbol) in answer.iter().enumerate() {
+                block.symbols[slot][lane] = symbol;
+                block.counts[symbol as usize][lane] += 1;
+            }
+        }
+        block
+    }
+
+    /// The (correct_positions, correct_symbols) of every answer of the block against `guess`.
+    /// `guess_counts` has the distinct symbols of the guess and how many times each appears
+    #[cfg(feature = "simd")]
+    fn compare(&self, guess: PossibleAnswer, guess_counts: &[(u8, u8)]) -> (Lanes, Lanes) {
+        use core::simd::prelude::*;
+        let mut correct_positions = u8x16::splat(0);
+        for (slot, &symbol) in guess.iter().enumerate() {
+            let equal = u8x16::from_array(self.symbols[slot]).simd_eq(u8x16::splat(symbol));
+            correct_positions += equal.select(u8x16::splat(1), u8x16::splat(0));
+        }
+        let mut common = u8x16::splat(0);
+        for &(symbol, count) in guess_counts {
+            common += u8x16::from_array(self.counts[symbol as usize]).simd_min(u8x16::splat(count));
+        }
+        (
+            correct_positions.to_array(),
+            (common - correct_positions).to_array(),
+        )
+    }
+
+    /// The (correct_positions, correct_symbols) of every answer of the block against `guess`.
+    /// `guess_counts` has the distinct symbols of the guess and how many times each appears
+    #[cfg(not(feature = "simd"))]
+    fn compare(&self, guess: PossibleAnswer, guess_counts: &[(u8, u8)]) -> (Lanes, Lanes) {
+        let mut correct_positions = [0u8; LANES];
+        for (slot, &symbol) in guess.iter().enumerate() {
+            for (positions, &answer_symbol) in correct_positions.iter_mut().zip(&self.symbols[slot])
+            {
+                *positions += (answer_symbol == symbol) as u8;
+            }
+        }
+        let mut common = [0u8; LANES];
+        for &(symbol, count) in guess_counts {
+            for (common, &answer_count) in common.iter_mut().zip(&self.counts[symbol as usize]) {
+                *common += answer_count.min(count);
+            }
+        }
+        let mut correct_symbols = [0u8; LANES];
+        for lane in 0..LANES {
+            correct_symbols[lane] = common[lane] - correct_positions[lane];
+        }
+        (correct_positions, correct_symbols)
+    }
+}
+
+/// A list of answers prepared to be compared against many guesses
+pub struct PackedAnswers {
+    len: usize,
+    blocks: Vec<Block>,
+}
+
+impl PackedAnswers {
+    pub fn new(answers: &[PossibleAnswer]) -> Self {
+        Self {
+            len: answers.len(),
+            blocks: answers.chunks(LANES).map(Block::new).collect(),
+        }
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Compare `guess` against every answer, calling `f` with the index of the answer and the
+    /// (correct_positions, correct_symbols) result
+    pub fn for_each_result(&self, guess: PossibleAnswer, mut f: impl FnMut(usize, u8, u8)) {
+        let mut counts = [0u8; SYMBOL_COUNT];
+        for symbol in guess {
+            counts[symbol as usize] += 1;
+        }
+        // At most 4 distinct symbols, so at most 4 mins per block
+        let mut guess_counts = [(0u8, 0u8); ANSWER_SIZE];
+        let mut distinct = 0;
+        for (symbol, &count) in counts.iter().enumerate().filter(|(_, &count)| count > 0) {
+            guess_counts[distinct] = (symbol as u8, count);
+            distinct += 1;
+        }
+        let guess_counts = &guess_counts[..distinct];
+
+        for (block_idx, block) in self.blocks.iter().enumerate() {
+            let (correct_positions, correct_symbols) = block.compare(guess, guess_counts);
+            let first = block_idx * LANES;
+            for lane in 0..LANES.min(self.len - first) {
+                f(first + lane, correct_positions[lane], correct_symbols[lane]);
+            }
+        }
+    }
+    /// Compare `guess` against every answer and count how many got each result
+    pub fn histogram(&self, guess: PossibleAnswer) -> ResultHistogram {
+        let mut histogram = [[0u32; ANSWER_SIZE + 1]; ANSWER_SIZE + 1];
+        self.for_each_result(guess, |_, correct_positions, correct_symbols| {
+            histogram[correct_positions as usize][correct_symbols as usize] += 1;
+        });
+        histogram
+    }
+}
+
+/// Compare `guess` against every answer in `answers`, and count how many answers got each result.
+/// Same as calling [crate::compare] for each of them, but faster
+pub fn compare_many(guess: PossibleAnswer, answers: &[PossibleAnswer]) -> ResultHistogram {
+    PackedAnswers::new(answers).histogram(guess)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{compare, compare2, idx_to_answer, POSSIBLE_ANSWERS};
+
+    #[test]
+    fn matches_compare() {
+        let answers = (0..POSSIBLE_ANSWERS).map(idx_to_answer).collect::<Vec<_>>();
+        let packed = PackedAnswers::new(&answers);
+        let mut rng = crate::genetic::SplitMix64::new(1234);
+        let guesses = [
+            [0, 0, 1, 2],
+            [11, 11, 11, 11],
+            [3, 1, 0, 4],
+            rng.answer(),
+            rng.answer(),
+        ];
+        for guess in guesses {
+            let mut expected = [[0u32; ANSWER_SIZE + 1]; ANSWER_SIZE + 1];
+            for &answer in answers.iter() {
+                let (p, s) = compare(&[answer, guess]);
+                assert_eq!(compare2(&[answer, guess]), (p, s));
+                expected[p as usize][s as usize] += 1;
+            }
+            assert_eq!(compare_many(guess, &answers), expected);
+            let mut compared = 0;
+            packed.for_each_result(guess, |idx, p, s| {
+                assert_eq!(compare(&[answers[idx], guess]), (p, s));
+                compared += 1;
+            });
+            assert_eq!(compared, answers.len());
+            // A partial last block
+            let some = &answers[1000..1037];
+            let mut expected = [[0u32; ANSWER_SIZE + 1]; ANSWER_SIZE + 1];
+            for &answer in some.iter() {
+                let (p, s) = compare(&[answer, guess]);
+                expected[p as usize][s as usize] += 1;
+            }
+            assert_eq!(compare_many(guess, some), expected);
+        }
+        assert!(PackedAnswers::new(&[]).is_empty());
+        assert_eq!(
+            compare_many([0, 0, 1, 2], &[]),
+            [[0; ANSWER_SIZE + 1]; ANSWER_SIZE + 1]
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! Persistent on-disk memoisation of computed guesses.
+//!
+//! Most vaults go through the same first few guesses and results, so instead of running the same
+//! searches again every time the program starts we remember them in a small text file. The file
+//! starts with a header line with the strategy id and version, followed by one
+//! `<history key> <guess idx>` line per known guess. If the header doesn't match the strategy we
+//! were created for, the file is considered stale and gets replaced the next time we store a guess.
+use crate::{
+    answer_to_idx, idx_to_answer, PartialAnswer, PossibleAnswer, Strategy, POSSIBLE_ANSWERS,
+};
+
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+const HEADER_PREFIX: &str = "nickracker-guess-cache";
+
+pub struct GuessCache {
+    path: PathBuf,
+    strategy_id: String,
+    strategy_version: u32,
+    /// Loaded from the file on first use. None until then
+    entries: Option<HashMap<u64, PossibleAnswer>>,
+    /// Whether the file on disk exists and has a header matching our strategy, so new entries can
+    /// just be appended to it
+    file_is_current: bool,
+}
+
+impl GuessCache {
+    /// A cache for the guesses [crate::SolverContext::guess] makes with the given strategy
+    pub fn new(path: impl Into<PathBuf>, strategy: Strategy) -> Self {
+        Self::for_strategy(path, &strategy.id(), strategy.version())
+    }
+    pub fn for_strategy(
+        path: impl Into<PathBuf>,
+        strategy_id: &str,
+        strategy_version: u32,
+    ) -> Self {
+        debug_assert!(!strategy_id.contains(char::is_whitespace));
+        Self {
+            path: path.into(),
+            strategy_id: strategy_id.to_string(),
+            strategy_version,
+            entries: None,
+            file_is_current: false,
+        }
+    }
+    pub fn strategy_id(&self) -> &str {
+        &self.strategy_id
+    }
+    pub fn strategy_version(&self) -> u32 {
+        self.strategy_version
+    }
+    fn header(&self) -> String {
+        format!(
+            "{} {} {}",
+            HEADER_PREFIX, self.strategy_id, self.strategy_version
+        )
+    }
+    /// Canonical key for a history of (guess, correct_positions, correct_symbols) results. The
+    /// order in which results were applied doesn't change the remaining possibilities, so it
+    /// doesn't change the key either. The length does: the guess depends on the attempt number
+    /// (Fixed openings, going for the win near the end), so repeated results are kept
+    pub fn key_for(&self, history: &[(PartialAnswer, u8, u8)]) -> u64 {
+        let mut history = history.to_vec();
+        history.sort_unstable();
+
+        // FNV-1a, since std's hashers are not guaranteed to be stable between releases
+        let mut hash = 0xcbf29ce484222325u64;
+        let mut write = |byte: u8| {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        };
+        for &byte in self.strategy_id.as_bytes() {
+            write(byte);
+        }
+        for byte in (history.len() as u64).to_le_bytes() {
+            write(byte);
+        }
+        for (guess, correct_positions, correct_symbols) in history {
+            for symbol in guess {
+                // Symbols are always below u8::MAX, so unknown ones can't collide with them
+                write(symbol.unwrap_or(u8::MAX));
+            }
+            write(correct_positions);
+            write(correct_symbols);
+        }
+        hash
+    }
+    fn entries(&mut self) -> &mut HashMap<u64, PossibleAnswer> {
+        if self.entries.is_none() {
+            self.entries = Some(self.load());
+        }
+        self.entries.as_mut().unwrap()
+    }
+    fn load(&mut self) -> HashMap<u64, PossibleAnswer> {
+        let mut entries = HashMap::new();
+        let contents = match std::fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(_) => return entries,
+        };
+        let mut lines = contents.lines();
+        if lines.next() != Some(self.header().as_str()) {
+            return entries;
+        }
+        self.file_is_current = true;
+        for line in lines {
+            let mut parts = line.split_whitespace();
+            let key = parts.next().and_then(|x| u64::from_str_radix(x, 16).ok());
+            let idx = parts.next().and_then(|x| x.parse::<usize>().ok());
+            // Skip anything malformed instead of throwing away the whole cache
+            if let (Some(key), Some(idx)) = (key, idx) {
+                if idx < POSSIBLE_ANSWERS {
+                    entries.insert(key, idx_to_answer(idx));
+                }
+            }
+        }
+        entries
+    }
+    pub fn get(&mut self, key: u64) -> Option<PossibleAnswer> {
+        self.entries().get(&key).copied()
+    }
+    /// Remember a guess, both in memory and in the cache file
+    pub fn insert(&mut self, key: u64, guess: PossibleAnswer) -> std::io::Result<()> {
+        self.entries().insert(key, guess);
+
+        if self.file_is_current {
+            let mut file = std::fs::OpenOptions::new().append(true).open(&self.path)?;
+            writeln!(file, "{:016x} {}", key, answer_to_idx(guess))?;
+        } else {
+            // Missing or stale file, write everything we have from scratch
+            let mut contents = self.header();
+            contents.push('\n');
+            for (key, guess) in self.entries.as_ref().unwrap() {
+                contents += &format!("{:016x} {}\n", key, answer_to_idx(*guess));
+            }
+            std::fs::write(&self.path, contents)?;
+            self.file_is_current = true;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "nickracker_cache_test_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn key_ignores_order() {
+        let cache = GuessCache::new(temp_path("key"), Strategy::Minimax);
+        let a = ([Some(0), Some(0), Some(1), Some(2)], 1, 0);
+        let b = ([Some(3), Some(3), Some(4), Some(4)], 0, 2);
+        let partial = ([Some(0), None, Some(1), Some(2)], 1, 0);
+        assert_eq!(cache.key_for(&[a, b]), cache.key_for(&[b, a]));
+        assert_ne!(cache.key_for(&[a, b]), cache.key_for(&[b, a, b]));
+        assert_ne!(cache.key_for(&[a]), cache.key_for(&[b]));
+        assert_ne!(cache.key_for(&[a]), cache.key_for(&[partial]));
+
+        let other = GuessCache::for_strategy(temp_path("key"), "other", 1);
+        assert_ne!(cache.key_for(&[a]), other.key_for(&[a]));
+    }
+
+    #[test]
+    fn key_depends_on_the_attempt() {
+        let cache = GuessCache::new(temp_path("attempt"), Strategy::Minimax);
+        let a = ([Some(0), Some(0), Some(1), Some(2)], 1, 0);
+        // Same results, so the same candidates, but not the same attempt
+        assert_ne!(cache.key_for(&[a]), cache.key_for(&[a, a]));
+        assert_ne!(cache.key_for(&[a, a]), cache.key_for(&[a, a, a]));
+
+        let path = temp_path("attempt_guess");
+        let mut cache = GuessCache::new(&path, Strategy::Minimax);
+        let mut solver = crate::SolverContext::new();
+        solver.apply_result([0, 0, 1, 2], 1, 0);
+        assert_eq!(solver.guess_cached(&mut cache), [3, 3, 4, 4]);
+        solver.apply_result([0, 0, 1, 2], 1, 0);
+        assert_eq!(solver.guess_cached(&mut cache), [5, 5, 6, 6]);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn persists_and_invalidates() {
+        let path = temp_path("persist");
+        let mut cache = GuessCache::for_strategy(&path, "test", 1);
+        cache.insert(1, [1, 2, 3, 4]).unwrap();
+        cache.insert(2, [11, 10, 9, 8]).unwrap();
+
+        let mut reloaded = GuessCache::for_strategy(&path, "test", 1);
+        assert_eq!(reloaded.get(1), Some([1, 2, 3, 4]));
+        assert_eq!(reloaded.get(2), Some([11, 10, 9, 8]));
+
+        let mut bumped = GuessCache::for_strategy(&path, "test", 2);
+        assert_eq!(bumped.get(1), None);
+        bumped.insert(3, [0, 0, 0, 0]).unwrap();
+        let mut reloaded = GuessCache::for_strategy(&path, "test", 2);
+        assert_eq!(reloaded.get(1), None);
+        assert_eq!(reloaded.get(3), Some([0, 0, 0, 0]));
+
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn guess_cached_matches_guess() {
+        let path = temp_path("guess");
+        let mut cache = GuessCache::new(&path, Strategy::Minimax);
+        let mut solver = crate::SolverContext::new();
+        for secret in [[0, 5, 0, 3], [3, 1, 0, 4]] {
+            solver.reset();
+            for _ in 0..4 {
+                let guess = solver.guess();
+                assert_eq!(solver.guess_cached(&mut cache), guess);
+                let res = crate::compare(&[secret, guess]);
+                solver.apply_result(guess, res.0, res.1);
+            }
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn guess_cached_ignores_other_strategies() {
+        let path = temp_path("other_strategy");
+        let mut solver = crate::SolverContext::new();
+        for guess in [[0, 0, 1, 2], [3, 3, 4, 4], [5, 5, 6, 6]] {
+            solver.apply_result(guess, 0, 1);
+        }
+        let key = GuessCache::new(&path, Strategy::Minimax).key_for(solver.history());
+        let guess = solver.guess();
+        // A cache of another strategy, or of an older version of this one
+        for mut cache in [
+            GuessCache::new(&path, Strategy::ExpectedSize),
+            GuessCache::for_strategy(
+                &path,
+                &Strategy::Minimax.id(),
+                Strategy::Minimax.version() + 1,
+            ),
+        ] {
+            cache.insert(key, [11, 11, 11, 11]).unwrap();
+            assert_eq!(solver.guess_cached(&mut cache), guess);
+        }
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,173 @@
+//! Printable cheat sheets, for people who can't run the overlay but can follow a strategy on
+//! paper.
+//!
+//! The sheet is generated from a [TreeNode]: the first guess, then for every possible result the
+//! guess to make next, and so on as deep as the tree was expanded. Symbols are written with the
+//! given human readable names, and a legend maps each name to a longer description.
+use crate::{tree::TreeNode, PossibleAnswer, SYMBOL_COUNT};
+
+use alloc::{format, string::String, string::ToString, vec::Vec};
+use core::fmt::Write;
+
+/// How symbols are written in a cheat sheet
+pub struct SymbolNames<'a> {
+    /// Used in the guesses, so these should be short
+    pub names: &'a [&'a str; SYMBOL_COUNT],
+    /// Used in the legend, to explain what each name means
+    pub descriptions: &'a [&'a str; SYMBOL_COUNT],
+}
+
+impl SymbolNames<'_> {
+    fn guess(&self, guess: PossibleAnswer) -> String {
+        guess
+            .iter()
+            .map(|&symbol| self.names[symbol as usize])
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn possibilities(count: usize) -> String {
+    if count == 1 {
+        "only 1 possible secret left".to_string()
+    } else {
+        format!("{} possible secrets", count)
+    }
+}
+
+fn escape_markdown(text: &str) -> String {
+    text.replace('|', "\\|").replace('*', "\\*")
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Markdown cheat sheet, as a nested list with one item per guess
+pub fn to_markdown(tree: &TreeNode, names: &SymbolNames) -> String {
+    fn write_node(out: &mut String, node: &TreeNode, names: &SymbolNames, indent: usize) {
+        for ((p, s), child) in node.children.iter() {
+            let _ = writeln!(
+                out,
+                "{:indent$}- **{},{}**: play **{}** ({})",
+                "",
+                p,
+                s,
+                escape_markdown(&names.guess(child.guess)),
+                possibilities(child.candidates),
+                indent = indent
+            );
+            write_node(out, child, names, indent + 4);
+        }
+    }
+
+    let mut out = String::from("# Minotaur vault cheat sheet\n\n");
+    out.push_str("| Name | Symbol |\n| --- | --- |\n");
+    for (name, description) in names.names.iter().zip(names.descriptions.iter()) {
+        let _ = writeln!(
+            out,
+            "| {} | {} |",
+            escape_markdown(name),
+            escape_markdown(description)
+        );
+    }
+    out.push_str(
+        "\nStart with the first guess. After each guess, find the result the vault gave you \
+         (correct positions, correct symbols) under it and play the guess it points to.\n\n",
+    );
+    let _ = writeln!(
+        out,
+        "1. Play **{}** ({})",
+        escape_markdown(&names.guess(tree.guess)),
+        possibilities(tree.candidates)
+    );
+    write_node(&mut out, tree, names, 4);
+    out
+}
+
+/// Standalone HTML page with the same contents as [to_markdown]
+pub fn to_html(tree: &TreeNode, names: &SymbolNames) -> String {
+    fn write_node(out: &mut String, node: &TreeNode, names: &SymbolNames) {
+        if node.children.is_empty() {
+            return;
+        }
+        out.push_str("<ul>\n");
+        for ((p, s), child) in node.children.iter() {
+            let _ = write!(
+                out,
+                "<li><b>{},{}</b>: play <b>{}</b> ({})",
+                p,
+                s,
+                escape_html(&names.guess(child.guess)),
+                possibilities(child.candidates)
+            );
+            write_node(out, child, names);
+            out.push_str("</li>\n");
+        }
+        out.push_str("</ul>\n");
+    }
+
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>Minotaur vault cheat sheet</title>\n\
+         <style>body { font-family: sans-serif; } li { margin: 2px 0; } \
+         table { border-collapse: collapse; } td, th { border: 1px solid #888; padding: 2px 6px; }\
+         </style>\n</head>\n<body>\n<h1>Minotaur vault cheat sheet</h1>\n",
+    );
+    out.push_str("<table>\n<tr><th>Name</th><th>Symbol</th></tr>\n");
+    for (name, description) in names.names.iter().zip(names.descriptions.iter()) {
+        let _ = writeln!(
+            out,
+            "<tr><td>{}</td><td>{}</td></tr>",
+            escape_html(name),
+            escape_html(description)
+        );
+    }
+    out.push_str(
+        "</table>\n<p>Start with the first guess. After each guess, find the result the vault \
+         gave you (correct positions, correct symbols) under it and play the guess it points \
+         to.</p>\n",
+    );
+    let _ = write!(
+        out,
+        "<ol>\n<li>Play <b>{}</b> ({})",
+        escape_html(&names.guess(tree.guess)),
+        possibilities(tree.candidates)
+    );
+    write_node(&mut out, tree, names);
+    out.push_str("</li>\n</ol>\n</body>\n</html>\n");
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const NAMES: [&str; SYMBOL_COUNT] = [
+        "a", "b", "c", "d", "><", "f", "g", "h", "i", "j", "|k|", "l",
+    ];
+
+    #[test]
+    fn sheets() {
+        let mut ctx = crate::SolverContext::new();
+        ctx.apply_result([0, 0, 1, 2], 0, 0);
+        let tree = TreeNode::build(&ctx, 2);
+        let names = SymbolNames {
+            names: &NAMES,
+            descriptions: &NAMES,
+        };
+
+        let markdown = to_markdown(&tree, &names);
+        assert!(markdown.contains("1. Play **d d >< ><** (6561 possible secrets)"));
+        assert!(markdown.contains("| \\|k\\| | \\|k\\| |"));
+        assert_eq!(markdown.matches("\n    - **").count(), tree.children.len());
+
+        let html = to_html(&tree, &names);
+        assert!(html.contains("&gt;&lt;"));
+        assert!(!html.contains("><<"));
+        assert_eq!(html.matches("<li>").count(), tree.node_count());
+    }
+}
@@ -0,0 +1,134 @@
+//! Completing a guess the player already started selecting.
+//!
+//! In the game the player picks the 4 symbols of a guess one by one. If they already picked some
+//! symbols that don't match the suggested guess, starting over is annoying, so instead we look for
+//! the best guess that keeps the symbols they picked. Guesses are compared by their worst case:
+//! the most secrets that could be left after making them.
+use crate::{
+    batch::PackedAnswers, idx_to_answer, tie_break::BestGuess, PossibleAnswer, SolverContext,
+    ANSWER_SIZE, SYMBOL_COUNT,
+};
+
+use alloc::vec::Vec;
+
+/// The result of [SolverContext::complete_guess]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Completion {
+    /// The best guess that keeps every selected symbol
+    pub guess: PossibleAnswer,
+    /// The most secrets that could be left after making `guess`
+    pub worst_case: usize,
+    /// The guess [SolverContext::guess] would make, ignoring the selected symbols
+    pub best_guess: PossibleAnswer,
+    /// The most secrets that could be left after making `best_guess`
+    pub best_worst_case: usize,
+}
+
+impl Completion {
+    /// How many more secrets could be left in the worst case by playing the completion instead of
+    /// the best guess. Can be negative, since strategies don't all minimize the worst case
+    pub fn extra_worst_case(&self) -> isize {
+        self.worst_case as isize - self.best_worst_case as isize
+    }
+}
+
+fn worst_case(packed: &PackedAnswers, guess: PossibleAnswer) -> usize {
+    packed
+        .histogram(guess)
+        .iter()
+        .flatten()
+        .map(|&count| count as usize)
+        .max()
+        .unwrap_or(0)
+}
+
+impl SolverContext {
+    /// Find the best way to fill the empty (None) slots of `selected`, and compare it with the
+    /// guess the strategy would make. Between completions with the same worst case, ones that
+    /// could be the secret are preferred, then the [crate::tie_break::TieBreak] decides. None if
+    /// a selected symbol is out of range or no secret is consistent with the history.
+    ///
+    /// Every possible completion is tried, so with few selected symbols and many remaining
+    /// secrets this is slower than [SolverContext::guess]
+    pub fn complete_guess(&self, selected: [Option<u8>; ANSWER_SIZE]) -> Option<Completion> {
+        if selected
+            .iter()
+            .flatten()
+            .any(|&symbol| symbol as usize >= SYMBOL_COUNT)
+        {
+            return None;
+        }
+        let candidates = self
+            .candidate_indices()
+            .map(idx_to_answer)
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            return None;
+        }
+        let packed = PackedAnswers::new(&candidates);
+
+        let free_slots = selected.iter().filter(|slot| slot.is_none()).count();
+        let mut best = BestGuess::new(self);
+        for n in 0..SYMBOL_COUNT.pow(free_slots as u32) {
+            // Count through the completions in base 12, one digit per free slot
+            let mut digits = idx_to_answer(n).into_iter();
+            let mut guess = [0; ANSWER_SIZE];
+            for (symbol, selected) in guess.iter_mut().zip(selected.iter()) {
+                *symbol = match selected {
+                    Some(selected) => *selected,
+                    None => digits.next().unwrap(),
+                };
+            }
+            best.offer(
+                guess,
+                (worst_case(&packed, guess), !self.is_consistent(guess)),
+            );
+        }
+        let guess = best.best()?;
+
+        let best_guess = self.clone().guess();
+        Some(Completion {
+            guess,
+            worst_case: worst_case(&packed, guess),
+            best_guess,
+            best_worst_case: worst_case(&packed, best_guess),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ctx() -> SolverContext {
+        let mut ctx = SolverContext::new();
+        ctx.apply_result([0, 0, 1, 2], 0, 1);
+        ctx.apply_result([3, 3, 4, 4], 0, 1);
+        ctx.apply_result([5, 5, 6, 6], 1, 0);
+        ctx
+    }
+
+    #[test]
+    fn keeps_selected_symbols() {
+        let ctx = ctx();
+        let completion = ctx.complete_guess([Some(7), None, Some(9), None]).unwrap();
+        assert_eq!(completion.guess[0], 7);
+        assert_eq!(completion.guess[2], 9);
+        assert_eq!(completion.best_guess, ctx.clone().guess());
+        assert!(completion.worst_case <= ctx.remaining_count());
+
+        // Nothing left to complete
+        let full = ctx.complete_guess(completion.guess.map(Some)).unwrap();
+        assert_eq!(full, completion);
+
+        assert_eq!(ctx.complete_guess([Some(12), None, None, None]), None);
+    }
+
+    #[test]
+    fn no_selection_is_at_least_as_good() {
+        let ctx = ctx();
+        let completion = ctx.complete_guess([None; ANSWER_SIZE]).unwrap();
+        // Every code was tried, including the best guess
+        assert!(completion.extra_worst_case() <= 0);
+    }
+}
@@ -0,0 +1,323 @@
+//! Checking that a recorded history is possible, and finding what was probably misread if not.
+//!
+//! Histories read from screenshots can have mistakes: a symbol matched to the wrong template or a
+//! result digit misread. When no secret is consistent with a history, [find_repairs] looks for the
+//! fewest changes (To a guess symbol or a result digit) that make it consistent again.
+//!
+//! For every possible secret we compute the cheapest way to fix each entry that doesn't match it:
+//! changing a single result digit or a single guess symbol costs 1, and if neither works we change
+//! both result digits, which costs 2 (And always works). The cheapest sets of changes over all
+//! secrets are then ranked by how many secrets they leave, since with every secret equally likely
+//! that's how likely each set of changes is to be the right one.
+//!
+//! The number of sets of changes grows exponentially with the number of entries to fix, so secrets
+//! that would need more than [MAX_MISREAD_ENTRIES] of them are not considered.
+use crate::{
+    idx_to_answer, partial_compare, PartialAnswer, PossibleAnswer, ANSWER_SIZE, POSSIBLE_ANSWERS,
+    SYMBOL_COUNT,
+};
+
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+/// Most history entries [find_repairs] assumes were misread
+pub const MAX_MISREAD_ENTRIES: usize = 2;
+
+/// One change to a history entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Correction {
+    /// Symbol `slot` of the guess of entry `entry` should be `symbol`
+    Symbol {
+        entry: usize,
+        slot: usize,
+        symbol: u8,
+    },
+    /// The result of entry `entry` should be (correct_positions, correct_symbols)
+    Result {
+        entry: usize,
+        correct_positions: u8,
+        correct_symbols: u8,
+    },
+}
+
+/// A set of corrections that makes a history consistent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    /// Sorted by entry
+    pub corrections: Vec<Correction>,
+    /// Number of changed symbols and result digits
+    pub cost: usize,
+    /// Number of secrets consistent with the repaired history. Higher means more plausible
+    pub secrets: usize,
+}
+
+impl Repair {
+    /// The history with the corrections applied
+    pub fn apply(&self, history: &[(PartialAnswer, u8, u8)]) -> Vec<(PartialAnswer, u8, u8)> {
+        let mut history = history.to_vec();
+        for correction in self.corrections.iter() {
+            match *correction {
+                Correction::Symbol {
+                    entry,
+                    slot,
+                    symbol,
+                } => history[entry].0[slot] = Some(symbol),
+                Correction::Result {
+                    entry,
+                    correct_positions,
+                    correct_symbols,
+                } => {
+                    history[entry].1 = correct_positions;
+                    history[entry].2 = correct_symbols;
+                }
+            }
+        }
+        history
+    }
+}
+
+fn matches(secret: PossibleAnswer, &(guess, p, s): &(PartialAnswer, u8, u8)) -> bool {
+    partial_compare(secret, guess).any(|result| result == (p, s))
+}
+
+/// Whether at least one secret is consistent with every entry of the history
+pub fn is_consistent(history: &[(PartialAnswer, u8, u8)]) -> bool {
+    (0..POSSIBLE_ANSWERS)
+        .map(idx_to_answer)
+        .any(|secret| history.iter().all(|entry| matches(secret, entry)))
+}
+
+/// The cheapest ways to make `entry` consistent with `secret`, and their cost
+fn entry_fixes(
+    secret: PossibleAnswer,
+    entry_idx: usize,
+    entry: &(PartialAnswer, u8, u8),
+) -> (usize, Vec<Correction>) {
+    let (guess, p, s) = *entry;
+    // With unknown symbols in the guess, several results are possible for the same secret
+    let mut results = partial_compare(secret, guess).collect::<Vec<_>>();
+    results.sort_unstable();
+    results.dedup();
+    let result_fix = |(correct_positions, correct_symbols)| Correction::Result {
+        entry: entry_idx,
+        correct_positions,
+        correct_symbols,
+    };
+    // Only one of the digits was misread
+    let mut fixes = results
+        .iter()
+        .filter(|&&(actual_p, actual_s)| actual_p == p || actual_s == s)
+        .map(|&result| result_fix(result))
+        .collect::<Vec<_>>();
+    // One of the known symbols was misread
+    for slot in 0..ANSWER_SIZE {
+        let read = match guess[slot] {
+            Some(read) => read,
+            None => continue,
+        };
+        for symbol in (0..SYMBOL_COUNT as u8).filter(|&symbol| symbol != read) {
+            let mut fixed = guess;
+            fixed[slot] = Some(symbol);
+            if matches(secret, &(fixed, p, s)) {
+                fixes.push(Correction::Symbol {
+                    entry: entry_idx,
+                    slot,
+                    symbol,
+                });
+            }
+        }
+    }
+    if fixes.is_empty() {
+        (2, results.into_iter().map(result_fix).collect())
+    } else {
+        (1, fixes)
+    }
+}
+
+/// The cheapest repairs of `history`, most plausible first and at most `limit` of them. Empty if
+/// the history is already consistent, or if it takes changes to more than
+/// [MAX_MISREAD_ENTRIES] entries
+pub fn find_repairs(history: &[(PartialAnswer, u8, u8)], limit: usize) -> Vec<Repair> {
+    // Per secret, the cost and the options to fix each entry that doesn't match it
+    let mut min_cost = usize::MAX;
+    let mut cheapest = Vec::new();
+    for secret in (0..POSSIBLE_ANSWERS).map(idx_to_answer) {
+        let mut cost = 0;
+        let mut options = Vec::new();
+        for (entry_idx, entry) in history.iter().enumerate() {
+            if matches(secret, entry) {
+                continue;
+            }
+            if options.len() == MAX_MISREAD_ENTRIES {
+                cost = usize::MAX;
+                break;
+            }
+            let (entry_cost, fixes) = entry_fixes(secret, entry_idx, entry);
+            cost += entry_cost;
+            if cost > min_cost {
+                break;
+            }
+            options.push(fixes);
+        }
+        if cost == 0 {
+            return Vec::new();
+        }
+        if cost == usize::MAX {
+            continue;
+        }
+        if cost < min_cost {
+            min_cost = cost;
+            cheapest.clear();
+        }
+        if cost == min_cost {
+            cheapest.push(options);
+        }
+    }
+
+    // Every combination of the options of a secret repairs the history for it. Count how many
+    // secrets each combination works for
+    let mut repairs = BTreeMap::<Vec<Correction>, usize>::new();
+    for options in cheapest.iter() {
+        let mut choice = vec![0; options.len()];
+        loop {
+            let corrections = options
+                .iter()
+                .zip(choice.iter())
+                .map(|(fixes, &i)| fixes[i])
+                .collect::<Vec<_>>();
+            *repairs.entry(corrections).or_insert(0) += 1;
+
+            // Next combination, like counting with a different base for each digit
+            let mut digit = 0;
+            while digit < choice.len() {
+                choice[digit] += 1;
+                if choice[digit] < options[digit].len() {
+                    break;
+                }
+                choice[digit] = 0;
+                digit += 1;
+            }
+            if digit == choice.len() {
+                break;
+            }
+        }
+    }
+
+    let mut repairs = repairs
+        .into_iter()
+        .map(|(corrections, secrets)| Repair {
+            corrections,
+            cost: min_cost,
+            secrets,
+        })
+        .collect::<Vec<_>>();
+    repairs.sort_by_key(|repair| core::cmp::Reverse(repair.secrets));
+    repairs.truncate(limit);
+    repairs
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::compare;
+
+    fn history_for(secret: PossibleAnswer) -> Vec<(PartialAnswer, u8, u8)> {
+        [[0, 0, 1, 2], [3, 3, 4, 4], [5, 5, 6, 6], [7, 8, 9, 10]]
+            .iter()
+            .map(|&guess| {
+                let (p, s) = compare(&[secret, guess]);
+                (guess.map(Some), p, s)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn consistent_history_needs_nothing() {
+        let history = history_for([1, 4, 6, 9]);
+        assert!(is_consistent(&history));
+        assert!(find_repairs(&history, 5).is_empty());
+    }
+
+    #[test]
+    fn finds_misread_result() {
+        let mut history = history_for([1, 4, 6, 9]);
+        // Impossible: 4 correct positions and 1 correct symbol
+        history[1].1 = 4;
+        history[1].2 = 1;
+        assert!(!is_consistent(&history));
+        let repairs = find_repairs(&history, 10);
+        assert!(!repairs.is_empty());
+        assert!(repairs.iter().all(|repair| repair.cost == repairs[0].cost));
+        assert!(repairs
+            .windows(2)
+            .all(|pair| pair[0].secrets >= pair[1].secrets));
+        for repair in repairs.iter() {
+            assert!(is_consistent(&repair.apply(&history)));
+        }
+        let original = history_for([1, 4, 6, 9]);
+        assert!(repairs
+            .iter()
+            .any(|repair| repair.apply(&history) == original));
+    }
+
+    #[test]
+    fn finds_misread_symbol() {
+        let secret = [2, 2, 5, 11];
+        let mut history = history_for(secret);
+        // The winning guess, with its last symbol misread
+        history.push(([2, 2, 5, 10].map(Some), 4, 0));
+        assert!(!is_consistent(&history));
+        let repairs = find_repairs(&history, 100);
+        assert_eq!(repairs[0].cost, 1);
+        assert!(repairs.iter().any(|repair| repair.corrections
+            == [Correction::Symbol {
+                entry: 4,
+                slot: 3,
+                symbol: 11
+            }]));
+        // Or [2, 2, 5, 10] was right, and the 0 correct symbols of [7, 8, 9, 10] was misread
+        assert!(repairs.iter().any(|repair| repair.corrections
+            == [Correction::Result {
+                entry: 3,
+                correct_positions: 1,
+                correct_symbols: 0
+            }]));
+    }
+
+    #[test]
+    fn too_many_misreads() {
+        let mut history = history_for([1, 4, 6, 9]);
+        // No secret gives 4 correct positions and 1 correct symbol, so every entry is wrong
+        for entry in history.iter_mut() {
+            entry.1 = 4;
+            entry.2 = 1;
+        }
+        assert!(!is_consistent(&history));
+        assert!(find_repairs(&history, 10).is_empty());
+        history.truncate(MAX_MISREAD_ENTRIES);
+        assert!(!find_repairs(&history, 10).is_empty());
+    }
+
+    #[test]
+    fn unknown_symbols_stay_unknown() {
+        let mut history = history_for([1, 4, 6, 9]);
+        history[2].0[0] = None;
+        assert!(is_consistent(&history));
+        // [?, 5, 6, 6] can't have 3 correct positions with 1 correct symbol
+        history[2].1 = 3;
+        history[2].2 = 1;
+        assert!(!is_consistent(&history));
+        let repairs = find_repairs(&history, 100);
+        assert!(!repairs.is_empty());
+        for repair in repairs.iter() {
+            assert!(!repair.corrections.iter().any(|correction| matches!(
+                correction,
+                Correction::Symbol {
+                    entry: 2,
+                    slot: 0,
+                    ..
+                }
+            )));
+            assert!(is_consistent(&repair.apply(&history)));
+        }
+    }
+}
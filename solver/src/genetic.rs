@@ -0,0 +1,256 @@
+//! Genetic algorithm guess generator, based on the approach described in "Genetic Algorithms
+//! Playing Mastermind" (Oijen, V. van, 2018) and the Berghman et al. algorithm it reviews.
+//!
+//! Instead of scoring every remaining candidate against every other one (Which is what makes the
+//! brute force strategies slow while there are still thousands of candidates), we evolve a
+//! population of codes towards codes that are consistent with the history, collect a limited
+//! number of those ("eligible" codes) and then pick the eligible code that best splits the others.
+use crate::{
+    idx_to_answer, partial_compare, PartialAnswer, PossibleAnswer, SolverContext, ANSWER_SIZE,
+    SYMBOL_COUNT,
+};
+
+use alloc::vec::Vec;
+
+/// Configuration for [crate::Strategy::Genetic]. The same configuration and history always give
+/// the same guess
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GeneticConfig {
+    /// Number of codes in each generation
+    pub population: u16,
+    /// Maximum number of generations to evolve before picking a guess
+    pub generations: u16,
+    /// Stop evolving once this many different consistent codes were found
+    pub max_eligible: u16,
+    pub seed: u64,
+}
+
+impl Default for GeneticConfig {
+    fn default() -> Self {
+        Self {
+            population: 150,
+            generations: 100,
+            max_eligible: 60,
+            seed: 0,
+        }
+    }
+}
+
+/// Small and fast deterministic PRNG (SplitMix64). Not suitable for anything security related
+#[derive(Debug, Clone)]
+pub struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+    /// Random number in 0..n
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+    /// True with the given probability, in percent
+    fn percent(&mut self, chance: usize) -> bool {
+        self.below(100) < chance
+    }
+    pub fn answer(&mut self) -> PossibleAnswer {
+        let mut answer = [0; ANSWER_SIZE];
+        for symbol in answer.iter_mut() {
+            *symbol = self.below(SYMBOL_COUNT) as u8;
+        }
+        answer
+    }
+}
+
+/// How far a code is from being consistent with the history. 0 means it could be the secret
+fn fitness(code: PossibleAnswer, history: &[(PartialAnswer, u8, u8)]) -> u32 {
+    history
+        .iter()
+        .map(|&(guess, correct_positions, correct_symbols)| {
+            // Guesses with unknown symbols count with whichever filling fits best
+            partial_compare(code, guess)
+                .map(|(p, s)| {
+                    (p as i32 - correct_positions as i32).unsigned_abs()
+                        + (s as i32 - correct_symbols as i32).unsigned_abs()
+                })
+                .min()
+                .unwrap_or(0)
+        })
+        .sum()
+}
+
+fn crossover(a: PossibleAnswer, b: PossibleAnswer, rng: &mut SplitMix64) -> PossibleAnswer {
+    let mut child = a;
+    if rng.percent(50) {
+        // 1 point crossover
+        let point = 1 + rng.below(ANSWER_SIZE - 1);
+        child[point..].copy_from_slice(&b[point..]);
+    } else {
+        // 2 point crossover
+        let first = rng.below(ANSWER_SIZE);
+        let last = first + rng.below(ANSWER_SIZE - first) + 1;
+        child[first..last].copy_from_slice(&b[first..last]);
+    }
+    child
+}
+
+fn mutate(code: &mut PossibleAnswer, rng: &mut SplitMix64) {
+    if rng.percent(3) {
+        // Change a symbol
+        code[rng.below(ANSWER_SIZE)] = rng.below(SYMBOL_COUNT) as u8;
+    }
+    if rng.percent(3) {
+        // Swap 2 symbols
+        code.swap(rng.below(ANSWER_SIZE), rng.below(ANSWER_SIZE));
+    }
+    if rng.percent(2) {
+        // Invert a section
+        let first = rng.below(ANSWER_SIZE);
+        let last = first + rng.below(ANSWER_SIZE - first) + 1;
+        code[first..last].reverse();
+    }
+}
+
+/// Pick the index of the better of 2 random codes from the population
+fn tournament(fitnesses: &[u32], rng: &mut SplitMix64) -> usize {
+    let a = rng.below(fitnesses.len());
+    let b = rng.below(fitnesses.len());
+    if fitnesses[b] < fitnesses[a] {
+        b
+    } else {
+        a
+    }
+}
+
+pub(crate) fn guess(ctx: &SolverContext, config: &GeneticConfig) -> PossibleAnswer {
+    if ctx.remaining_count() == 0 {
+        // No secret is consistent with the history, like minimax
+        return idx_to_answer(1);
+    }
+    let history = ctx.history();
+    let population_size = (config.population as usize).max(2);
+    // Mix in the attempt so we don't evolve the exact same way after every result
+    let mut rng = SplitMix64::new(config.seed ^ (history.len() as u64).wrapping_mul(0x2545f491));
+
+    let mut eligible: Vec<PossibleAnswer> = Vec::new();
+    let mut population = (0..population_size)
+        .map(|_| rng.answer())
+        .collect::<Vec<_>>();
+    let mut fitnesses = Vec::with_capacity(population_size);
+    let mut next_population = Vec::with_capacity(population_size);
+    for _ in 0..config.generations {
+        fitnesses.clear();
+        fitnesses.extend(population.iter().map(|&code| fitness(code, history)));
+        for (&code, &fitness) in population.iter().zip(fitnesses.iter()) {
+            if fitness == 0 && !eligible.contains(&code) {
+                eligible.push(code);
+            }
+        }
+        if eligible.len() >= config.max_eligible as usize {
+            break;
+        }
+
+        next_population.clear();
+        while next_population.len() < population_size {
+            let a = population[tournament(&fitnesses, &mut rng)];
+            let b = population[tournament(&fitnesses, &mut rng)];
+            let mut child = crossover(a, b, &mut rng);
+            mutate(&mut child, &mut rng);
+            // Duplicates make the population converge too early, replace them with random codes
+            if next_population.contains(&child) {
+                child = rng.answer();
+            }
+            next_population.push(child);
+        }
+        core::mem::swap(&mut population, &mut next_population);
+    }
+
+    if eligible.is_empty() {
+        // Evolution didn't find anything, any candidate is still a consistent guess
+        return ctx
+            .candidate_indices()
+            .next()
+            .map_or(idx_to_answer(1), idx_to_answer);
+    }
+    eligible.sort_unstable();
+
+    // Pick the eligible code that, used as a guess, would split the other eligible codes (Our
+    // sample of the remaining candidates) into the smallest expected group
+    let packed = crate::batch::PackedAnswers::new(&eligible);
+    let mut best = crate::tie_break::BestGuess::new(ctx);
+    for &guess in eligible.iter() {
+        let score = packed
+            .histogram(guess)
+            .iter()
+            .flatten()
+            .map(|&n| n as usize * n as usize)
+            .sum::<usize>();
+        best.offer(guess, score);
+    }
+    best.best().unwrap_or(eligible[0])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::compare;
+
+    #[test]
+    fn deterministic() {
+        let config = GeneticConfig::default();
+        let secret = [7, 2, 9, 2];
+        let mut a = SolverContext::with_strategy(crate::Strategy::Genetic(config));
+        let mut b = SolverContext::with_strategy(crate::Strategy::Genetic(config));
+        let mut solved = false;
+        for _ in 0..crate::MAX_GUESSES {
+            let guess = a.guess();
+            assert_eq!(b.guess(), guess);
+            // Past the fixed openings, guesses come from the candidates
+            if a.history().len() >= 3 {
+                assert!(a.is_consistent(guess));
+            }
+            if guess == secret {
+                solved = true;
+                break;
+            }
+            let res = compare(&[secret, guess]);
+            a.apply_result(guess, res.0, res.1);
+            b.apply_result(guess, res.0, res.1);
+        }
+        assert!(solved);
+    }
+
+    #[test]
+    fn no_candidates() {
+        let mut ctx = SolverContext::with_strategy(crate::Strategy::Genetic(Default::default()));
+        for guess in [[0, 0, 1, 2], [3, 3, 4, 4], [5, 5, 6, 6]] {
+            ctx.apply_result(guess, 0, 0);
+        }
+        ctx.apply_result([7, 8, 9, 10], 4, 0);
+        ctx.apply_result([7, 8, 9, 10], 0, 0);
+        assert_eq!(ctx.remaining_count(), 0);
+        guess(&ctx, &GeneticConfig::default());
+    }
+
+    #[test]
+    fn fitness_is_zero_for_candidates() {
+        let history = [
+            ([Some(0), Some(0), Some(1), Some(2)], 1, 1),
+            ([Some(3), Some(3), Some(4), Some(4)], 0, 1),
+        ];
+        let candidate = [0, 5, 0, 3];
+        assert_eq!(fitness(candidate, &history), 0);
+        assert!(fitness([11, 11, 11, 11], &history) > 0);
+        // The unknown symbol could have been a 5
+        let partial = [([None, Some(6), Some(7), Some(8)], 0, 1)];
+        assert_eq!(fitness(candidate, &partial), 0);
+        assert!(fitness([8, 7, 6, 1], &partial) > 0);
+    }
+}
@@ -1,3 +1,10 @@
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+// Tests always have std, for printing and threads
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+extern crate alloc;
+
+use alloc::{borrow::Cow, format, string::String, string::ToString, sync::Arc, vec, vec::Vec};
+
 // Ignore this macro, it is black magic
 macro_rules! vec_to_arr_box {
     (
@@ -6,7 +13,7 @@ macro_rules! vec_to_arr_box {
         const N: usize = $size_expr;
         let vec_expr = $vec_expr;
         {
-            use std::prelude::v1::*;
+            use alloc::{boxed::Box, vec::Vec};
 
             #[inline]
             fn with_ty<T>(v: Vec<T>) -> Box<[T; N]> {
@@ -24,8 +31,97 @@ macro_rules! vec_to_arr_box {
 pub const SYMBOL_COUNT: usize = 12;
 pub const ANSWER_SIZE: usize = 4;
 pub const POSSIBLE_ANSWERS: usize = SYMBOL_COUNT.pow(ANSWER_SIZE as u32);
+/// Guesses the vault allows before it locks
+pub const MAX_GUESSES: usize = 12;
 
 pub type PossibleAnswer = [u8; ANSWER_SIZE];
+/// A guess where some symbols may be unknown (None), like a past guess that couldn't be fully read
+/// from a screenshot
+pub type PartialAnswer = [Option<u8>; ANSWER_SIZE];
+
+/// The different ways [SolverContext::guess] can pick the next guess. The first 3 guesses always
+/// come from a fixed opening (See [SolverContext::guess]), strategies only differ after that. Near
+/// the end every strategy goes for the win instead (See [SolverContext::goes_for_the_win])
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Strategy {
+    /// Pick the guess that, in the worst case, leaves us with the smallest possible remaining set
+    /// of possible solutions. The default
+    Minimax,
+    /// Pick the guess that minimizes the expected number of remaining possible solutions. If a
+    /// [prior::Prior] is set, each possible secret is weighted by its probability
+    ExpectedSize,
+    /// Evolve consistent codes with a genetic algorithm instead of scoring every candidate. See
+    /// [genetic]
+    Genetic(genetic::GeneticConfig),
+}
+
+impl Default for Strategy {
+    fn default() -> Self {
+        Strategy::Minimax
+    }
+}
+
+impl Strategy {
+    /// Identifies the strategy (Including its configuration) for things that store its results
+    /// (Like the [cache::GuessCache]). Never contains whitespace
+    pub fn id(&self) -> String {
+        match self {
+            Strategy::Minimax => "minimax".to_string(),
+            Strategy::ExpectedSize => "expected-size".to_string(),
+            Strategy::Genetic(config) => format!(
+                "genetic-p{}-g{}-e{}-s{}",
+                config.population, config.generations, config.max_eligible, config.seed
+            ),
+        }
+    }
+    /// Inverse of [Strategy::id]
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "minimax" => Some(Strategy::Minimax),
+            "expected-size" => Some(Strategy::ExpectedSize),
+            _ => {
+                let fields = id.strip_prefix("genetic-")?.split('-').collect::<Vec<_>>();
+                let config = match fields.as_slice() {
+                    [population, generations, max_eligible, seed] => genetic::GeneticConfig {
+                        population: population.strip_prefix('p')?.parse().ok()?,
+                        generations: generations.strip_prefix('g')?.parse().ok()?,
+                        max_eligible: max_eligible.strip_prefix('e')?.parse().ok()?,
+                        seed: seed.strip_prefix('s')?.parse().ok()?,
+                    },
+                    _ => return None,
+                };
+                Some(Strategy::Genetic(config))
+            }
+        }
+    }
+    /// Bump this whenever a change to a strategy can change the guesses it makes, so stored
+    /// results get invalidated
+    pub fn version(&self) -> u32 {
+        match self {
+            Strategy::Minimax => 2,
+            Strategy::ExpectedSize => 2,
+            Strategy::Genetic(_) => 3,
+        }
+    }
+    /// Whether the guesses of this strategy depend on the [prior::Prior]
+    pub fn uses_prior(&self) -> bool {
+        matches!(self, Strategy::ExpectedSize)
+    }
+}
+
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod cache;
+pub mod cheatsheet;
+pub mod completion;
+pub mod consistency;
+pub mod genetic;
+pub mod posterior;
+pub mod prior;
+pub mod state;
+pub mod tie_break;
+pub mod tree;
 
 // We apply  Donald Knuth's algorithm
 // Implemented in SolverContext::apply_result(ans: PossibleAnswer, correct_positions: u8,
@@ -67,21 +163,16 @@ pub fn compare2(a_and_b: &[PossibleAnswer; 2]) -> (u8, u8) {
     }
     let mut a_symbol_counter = [0u8; SYMBOL_COUNT];
     let mut b_symbol_counter = [0u8; SYMBOL_COUNT];
-    for (aa, already_matched) in a.iter().zip(found_match_at_pos.iter()) {
+    // Same symbol, any position. Only count symbols that weren't already a positional match
+    for ((aa, bb), already_matched) in a.iter().zip(b.iter()).zip(found_match_at_pos.iter()) {
         if !already_matched {
             a_symbol_counter[*aa as usize] += 1;
-        }
-        // Same symbol, any position
-        for (bb, already_matched) in b.iter().zip(found_match_at_pos.iter()) {
-            if !already_matched && aa == bb {
-                b_symbol_counter[*bb as usize] += 1;
-                break;
-            }
+            b_symbol_counter[*bb as usize] += 1;
         }
     }
     let correct_symbols = a_symbol_counter
         .into_iter()
-        .zip(b_symbol_counter.into_iter())
+        .zip(b_symbol_counter)
         .map(|(a, b)| a.min(b))
         .sum::<u8>();
 
@@ -122,8 +213,61 @@ pub fn compare(a_and_b: &[PossibleAnswer; 2]) -> (u8, u8) {
 
     (correct_positions, correct_symbols)
 }
+
+/// Every way of filling the unknown symbols of `guess` that can compare differently against
+/// `secret`. Symbols that aren't in the secret all compare the same, so only one of them is tried
+pub fn fillings(
+    secret: PossibleAnswer,
+    guess: PartialAnswer,
+) -> impl Iterator<Item = PossibleAnswer> {
+    let mut symbols = [0u8; ANSWER_SIZE + 1];
+    let mut symbol_count = 0;
+    for &symbol in secret.iter() {
+        if !symbols[..symbol_count].contains(&symbol) {
+            symbols[symbol_count] = symbol;
+            symbol_count += 1;
+        }
+    }
+    // There are more symbols than slots, so there's always one that isn't in the secret
+    symbols[symbol_count] = (0..SYMBOL_COUNT as u8)
+        .find(|symbol| !secret.contains(symbol))
+        .unwrap();
+    symbol_count += 1;
+
+    let unknown = guess.iter().filter(|symbol| symbol.is_none()).count();
+    (0..symbol_count.pow(unknown as u32)).map(move |mut n| {
+        let mut filled = [0; ANSWER_SIZE];
+        for (out, symbol) in filled.iter_mut().zip(guess.iter()) {
+            *out = symbol.unwrap_or_else(|| {
+                let symbol = symbols[n % symbol_count];
+                n /= symbol_count;
+                symbol
+            });
+        }
+        filled
+    })
+}
+
+/// The (correct_positions, correct_symbols) results `guess` could get against `secret`, depending
+/// on its unknown symbols. Can have duplicates
+pub fn partial_compare(
+    secret: PossibleAnswer,
+    guess: PartialAnswer,
+) -> impl Iterator<Item = (u8, u8)> {
+    fillings(secret, guess).map(move |filled| compare(&[secret, filled]))
+}
+
+/// Cloning is cheap: the candidate storage is shared between clones until one of them applies a
+/// result (See [SolverContext::fork])
+#[derive(Clone)]
 pub struct SolverContext {
-    answers_known_to_be_false: Box<[bool; POSSIBLE_ANSWERS]>,
+    answers_known_to_be_false: Arc<[bool; POSSIBLE_ANSWERS]>,
+    /// The results applied so far, in order: The guess and (correct_positions, correct_symbols)
+    history: Vec<(PartialAnswer, u8, u8)>,
+    strategy: Strategy,
+    tie_break: tie_break::TieBreak,
+    /// None means every secret is equally likely
+    prior: Option<Arc<prior::Prior>>,
     attempt: u8,
     first_idx: usize,
     last_idx: usize,
@@ -141,31 +285,107 @@ impl SolverContext {
             answers_known_to_be_false: vec_to_arr_box!(
                 vec![false; POSSIBLE_ANSWERS],
                 POSSIBLE_ANSWERS
-            ),
+            )
+            .into(),
+            history: Vec::new(),
+            strategy: Strategy::default(),
+            tie_break: tie_break::TieBreak::default(),
+            prior: None,
             first_idx: 0,
             last_idx: POSSIBLE_ANSWERS,
             attempt: 0,
         }
     }
+    pub fn with_strategy(strategy: Strategy) -> Self {
+        let mut ctx = Self::new();
+        ctx.strategy = strategy;
+        ctx
+    }
+    pub fn history(&self) -> &[(PartialAnswer, u8, u8)] {
+        &self.history
+    }
+    pub fn strategy(&self) -> Strategy {
+        self.strategy
+    }
+    pub fn set_strategy(&mut self, strategy: Strategy) {
+        self.strategy = strategy;
+    }
+    pub fn tie_break(&self) -> tie_break::TieBreak {
+        self.tie_break
+    }
+    /// Set how the strategy picks between guesses with the same score. Like the strategy, this
+    /// survives resets
+    pub fn set_tie_break(&mut self, tie_break: tie_break::TieBreak) {
+        self.tie_break = tie_break;
+    }
+    pub fn prior(&self) -> Option<&prior::Prior> {
+        self.prior.as_deref()
+    }
+    /// Set the probability distribution of the secrets. None (Or a uniform prior) means every
+    /// secret is equally likely. Unlike [SolverContext::reset], this survives resets
+    pub fn set_prior(&mut self, prior: Option<prior::Prior>) {
+        self.prior = prior.filter(|prior| !prior.is_uniform()).map(Arc::new);
+    }
+    /// Number of secrets that are still possible
+    pub fn remaining_count(&self) -> usize {
+        self.candidate_indices().count()
+    }
+    /// Whether `answer` could still be the secret
+    pub fn is_consistent(&self, answer: PossibleAnswer) -> bool {
+        !self.answers_known_to_be_false[answer_to_idx(answer)]
+    }
+    pub(crate) fn candidate_indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.answers_known_to_be_false[self.first_idx..self.last_idx]
+            .iter()
+            .enumerate()
+            .filter(|&(_, &known_bad)| !known_bad)
+            .map(move |(idx, _)| idx + self.first_idx)
+    }
+    fn candidate_weight(&self, idx: usize) -> f64 {
+        self.prior
+            .as_ref()
+            .map_or(1.0, |prior| prior.weight_of_idx(idx))
+    }
     pub fn apply_result(
         &mut self,
         ans: PossibleAnswer,
         correct_positions: u8,
         correct_symbols: u8,
     ) {
-        self.attempt += 1;
+        self.apply_partial_result(ans.map(Some), correct_positions, correct_symbols)
+    }
+    /// Same as [SolverContext::apply_result], for a guess with unknown (None) symbols. A secret
+    /// stays possible if any way of filling the unknown symbols gives this result against it
+    pub fn apply_partial_result(
+        &mut self,
+        guess: PartialAnswer,
+        correct_positions: u8,
+        correct_symbols: u8,
+    ) {
+        self.attempt = self.attempt.saturating_add(1);
+        self.history
+            .push((guess, correct_positions, correct_symbols));
 
-        let mut compare_area = [ans, ans];
+        let full_guess =
+            if guess.iter().all(Option::is_some) { Some(guess.map(Option::unwrap)) } else { None };
 
-        for (idx, mut_ref_to_known_false) in self.answers_known_to_be_false
+        // Copy the candidates if they're shared with a fork
+        let answers_known_to_be_false = Arc::make_mut(&mut self.answers_known_to_be_false);
+        for (idx, mut_ref_to_known_false) in answers_known_to_be_false
             [self.first_idx..self.last_idx]
             .iter_mut()
             .enumerate()
             .filter(|&(_, &mut known_bad)| !known_bad)
         {
-            let idx = idx + self.first_idx;
-            compare_area[0] = idx_to_answer(idx);
-            if compare(&compare_area) != (correct_positions, correct_symbols) {
+            let secret = idx_to_answer(idx + self.first_idx);
+            let possible = match full_guess {
+                Some(full_guess) => {
+                    compare(&[secret, full_guess]) == (correct_positions, correct_symbols)
+                }
+                None => partial_compare(secret, guess)
+                    .any(|result| result == (correct_positions, correct_symbols)),
+            };
+            if !possible {
                 *mut_ref_to_known_false = true;
             }
         }
@@ -176,19 +396,25 @@ impl SolverContext {
             .filter(|&(_, &known_bad)| !known_bad)
             .map(|(idx, _)| idx)
             .next()
-            .unwrap();
+            // No secret is consistent with the history, leave an empty range
+            .unwrap_or(0);
         self.last_idx = self
             .answers_known_to_be_false
             .iter()
             .enumerate()
             .rev()
             .filter(|&(_, &known_bad)| !known_bad)
-            .map(|(idx, _)| idx)
+            .map(|(idx, _)| idx + 1)
             .next()
-            .unwrap()
-            + 1;
+            .unwrap_or(0);
     }
     pub fn guess(&mut self) -> PossibleAnswer {
+        // Near the end, trying the most likely secret beats learning more about the others
+        if self.goes_for_the_win() {
+            if let Some(guess) = self.guess_most_likely() {
+                return guess;
+            }
+        }
         // For the first attempt, I think any pattern of the form [A, A, B, B] is equally as good
         // Special case it because since it has the biggest search space (Full 12**4) it is the
         // slowest
@@ -202,61 +428,140 @@ impl SolverContext {
             //return [4,4,5,5];
             return [5, 5, 6, 6];
         }
+        match self.strategy {
+            Strategy::Minimax => self.guess_minimax(),
+            Strategy::ExpectedSize => self.guess_expected_size(),
+            Strategy::Genetic(config) => genetic::guess(self, &config),
+        }
+    }
+    fn guess_minimax(&self) -> PossibleAnswer {
         // We find the guess that, in the worst case, leaves us with the smallest possible
         // remaining set of possible solutions (We find it by brute force)
-        let mut guess = 1;
-        let mut possibilities_left_after = POSSIBLE_ANSWERS;
-        for (idx, _) in self.answers_known_to_be_false[self.first_idx..self.last_idx]
-            .iter()
-            .enumerate()
-            .filter(|&(_, &known_bad)| !known_bad)
-        {
-            let idx = idx + self.first_idx;
+        let candidates = self
+            .candidate_indices()
+            .map(idx_to_answer)
+            .collect::<Vec<_>>();
+        let packed = batch::PackedAnswers::new(&candidates);
+        let mut best = tie_break::BestGuess::new(self);
+        for &guess in self.scored_guesses(&candidates).iter() {
+            let histogram = packed.histogram(guess);
             let mut max_possible_answers = 0usize;
-            for correct_positions in 0..3 {
-                for correct_symbols in 0..4 {
+            for (correct_positions, row) in histogram.iter().enumerate().take(3) {
+                for (correct_symbols, &count) in row.iter().enumerate().take(4) {
                     if correct_positions + correct_symbols < 5 {
                         // Skip invalid
                         max_possible_answers =
-                            max_possible_answers.max(self.calc_guess_leftover_possibilities(
-                                idx,
-                                correct_positions,
-                                correct_symbols,
-                            ));
+                            max_possible_answers.max(candidates.len() - count as usize);
                     }
                 }
             }
-            if max_possible_answers < possibilities_left_after {
-                guess = idx;
-                possibilities_left_after = max_possible_answers;
-            }
+            best.offer(guess, max_possible_answers);
         }
-        idx_to_answer(guess)
+        best.best().unwrap_or(idx_to_answer(1))
     }
-    fn calc_guess_leftover_possibilities(
-        &self,
-        idx: usize,
-        correct_positions: u8,
-        correct_symbols: u8,
-    ) -> usize {
-        let mut compare_area = [idx_to_answer(idx), idx_to_answer(idx)];
-        let mut possible_answer_count = 0;
-
-        for (possible_idx, _) in self.answers_known_to_be_false[self.first_idx..self.last_idx]
+    fn guess_expected_size(&self) -> PossibleAnswer {
+        // Expected remaining count is sum(P(result) * count(result)) for every possible result.
+        // P(result) is the weight of the candidates giving that result over the total weight
+        let candidates = self.candidate_indices().collect::<Vec<_>>();
+        if candidates.is_empty() {
+            // No secret is consistent with the history, like minimax
+            return idx_to_answer(1);
+        }
+        let mut weights = candidates
             .iter()
-            .enumerate()
-            .filter(|&(_, &known_bad)| !known_bad)
+            .map(|&idx| self.candidate_weight(idx))
+            .collect::<Vec<_>>();
+        let mut total_weight = weights.iter().sum::<f64>();
+        if total_weight <= 0.0 {
+            // The prior ruled out every candidate that's left, so it can't tell them apart
+            weights.fill(1.0);
+            total_weight = weights.len() as f64;
+        }
+        let candidate_answers = candidates
+            .iter()
+            .map(|&idx| idx_to_answer(idx))
+            .collect::<Vec<_>>();
+        let packed = batch::PackedAnswers::new(&candidate_answers);
+
+        let mut best = tie_break::BestGuess::new(self);
+        for &guess in self.scored_guesses(&candidate_answers).iter() {
+            let mut counts = [[0usize; ANSWER_SIZE + 1]; ANSWER_SIZE + 1];
+            let mut result_weights = [[0f64; ANSWER_SIZE + 1]; ANSWER_SIZE + 1];
+            packed.for_each_result(guess, |i, correct_positions, correct_symbols| {
+                counts[correct_positions as usize][correct_symbols as usize] += 1;
+                result_weights[correct_positions as usize][correct_symbols as usize] += weights[i];
+            });
+            let expected_size = counts
+                .iter()
+                .flatten()
+                .zip(result_weights.iter().flatten())
+                .map(|(&count, &weight)| count as f64 * weight)
+                .sum::<f64>()
+                / total_weight;
+            best.offer(guess, expected_size);
+        }
+        best.best().unwrap_or(idx_to_answer(candidates[0]))
+    }
+    /// The guesses minimax and expected size score. Only the candidates, unless the tie break
+    /// prefers consistent codes: that only matters if codes that can't be the secret get a score
+    fn scored_guesses<'a>(&self, candidates: &'a [PossibleAnswer]) -> Cow<'a, [PossibleAnswer]> {
+        if self.tie_break == tie_break::TieBreak::PreferConsistent && !candidates.is_empty() {
+            Cow::Owned((0..POSSIBLE_ANSWERS).map(idx_to_answer).collect())
+        } else {
+            Cow::Borrowed(candidates)
+        }
+    }
+    /// Same as [SolverContext::guess], but looks the current history up in the given cache first
+    /// and stores newly computed guesses in it. A cache made for another strategy (Or another
+    /// version of it) isn't used at all
+    #[cfg(feature = "std")]
+    pub fn guess_cached(&mut self, cache: &mut cache::GuessCache) -> PossibleAnswer {
+        // A cache of another strategy (Or version) has that strategy's guesses. Guesses made with
+        // a non-uniform prior or tie break depend on them, and the cache doesn't know about them.
+        // Going for the win uses the prior whatever the strategy
+        if cache.strategy_id() != self.strategy.id()
+            || cache.strategy_version() != self.strategy.version()
+            || (self.prior.is_some() && (self.strategy.uses_prior() || self.goes_for_the_win()))
+            || self.tie_break != tie_break::TieBreak::default()
         {
-            let possible_idx = possible_idx + self.first_idx;
-            compare_area[0] = idx_to_answer(possible_idx);
-            if compare(&compare_area) != (correct_positions, correct_symbols) {
-                possible_answer_count += 1;
-            }
+            return self.guess();
         }
-        possible_answer_count
+        let key = cache.key_for(&self.history);
+        if let Some(guess) = cache.get(key) {
+            return guess;
+        }
+        let guess = self.guess();
+        // A cache we can't write to just means we compute this again next time
+        let _ = cache.insert(key, guess);
+        guess
+    }
+    /// An independent copy of this context. The candidates are shared with the original (So this
+    /// is cheap) until either of them applies a result
+    pub fn fork(&self) -> Self {
+        self.clone()
+    }
+    /// Run `f` on a fork of this context with an extra (correct_positions, correct_symbols)
+    /// result applied for `guess`, to see what would happen if we got that result
+    pub fn with_hypothetical<R>(
+        &self,
+        guess: PossibleAnswer,
+        (correct_positions, correct_symbols): (u8, u8),
+        f: impl FnOnce(&mut SolverContext) -> R,
+    ) -> R {
+        let mut fork = self.fork();
+        fork.apply_result(guess, correct_positions, correct_symbols);
+        f(&mut fork)
     }
     pub fn reset(&mut self) {
-        self.answers_known_to_be_false[..].fill(false);
+        match Arc::get_mut(&mut self.answers_known_to_be_false) {
+            Some(answers_known_to_be_false) => answers_known_to_be_false.fill(false),
+            // Shared with a fork, no need to copy it just to overwrite it
+            None => {
+                self.answers_known_to_be_false =
+                    vec_to_arr_box!(vec![false; POSSIBLE_ANSWERS], POSSIBLE_ANSWERS).into()
+            }
+        }
+        self.history.clear();
         self.attempt = 0;
         self.first_idx = 0;
         self.last_idx = POSSIBLE_ANSWERS;
@@ -289,11 +594,13 @@ impl SolverContext {
     }
 }
 
+#[cfg(feature = "std")]
 pub fn basic_test() {
     let mut state = SolverContext::new();
     let answer = [3, 4, 5, 1];
     dbg!(state.solve(answer));
 }
+#[cfg(feature = "std")]
 pub fn noisy_solve(secret: PossibleAnswer) {
     let mut state = SolverContext::new();
     let mut compare_area = [secret; 2];
@@ -370,50 +677,179 @@ mod test {
         */
     }
 
+    #[test]
+    fn solves_expected_size() {
+        let mut solver = SolverContext::with_strategy(Strategy::ExpectedSize);
+        solver.solve([0, 5, 0, 3]);
+        solver.solve([3, 1, 0, 4]);
+        solver.solve([5, 4, 2, 0]);
+    }
+    #[test]
+    fn expected_size_without_candidates_or_weight() {
+        let secret = [1, 4, 6, 9];
+        let mut ctx = SolverContext::with_strategy(Strategy::ExpectedSize);
+        // Only a secret these results rule out has any weight
+        ctx.set_prior(Some(
+            prior::Prior::from_weights([([0, 0, 1, 2], 1.0)]).unwrap(),
+        ));
+        for guess in [[0, 0, 1, 2], [3, 3, 4, 4], [5, 5, 6, 6]] {
+            let (p, s) = compare(&[secret, guess]);
+            ctx.apply_result(guess, p, s);
+        }
+        assert!(ctx.remaining_count() > 0);
+        assert!(ctx.is_consistent(ctx.guess_expected_size()));
+
+        // Contradicting results
+        ctx.apply_result(secret, 4, 0);
+        ctx.apply_result(secret, 0, 0);
+        assert_eq!(ctx.remaining_count(), 0);
+        ctx.guess_expected_size();
+    }
+    #[test]
+    fn uniform_prior_changes_nothing() {
+        let mut with_prior = SolverContext::with_strategy(Strategy::ExpectedSize);
+        with_prior.set_prior(Some(prior::Prior::uniform()));
+        assert!(with_prior.prior().is_none());
+
+        let mut without_prior = SolverContext::with_strategy(Strategy::ExpectedSize);
+        let secret = [5, 0, 2, 3];
+        for _ in 0..5 {
+            let guess = without_prior.guess();
+            assert_eq!(with_prior.guess(), guess);
+            let res = compare(&[secret, guess]);
+            without_prior.apply_result(guess, res.0, res.1);
+            with_prior.apply_result(guess, res.0, res.1);
+        }
+    }
+
+    #[test]
+    fn partial_results_keep_the_secret() {
+        let secret = [4, 9, 9, 1];
+        let mut full = SolverContext::new();
+        let mut partial = SolverContext::new();
+        for guess in [[0, 0, 1, 2], [3, 3, 4, 4], [9, 5, 6, 9]] {
+            let (p, s) = compare(&[secret, guess]);
+            full.apply_result(guess, p, s);
+            let mut unknown = guess.map(Some);
+            unknown[1] = None;
+            partial.apply_partial_result(unknown, p, s);
+        }
+        assert!(partial.is_consistent(secret));
+        // Less is known, so at least as many secrets are left, but it still rules some out
+        assert!(partial.remaining_count() >= full.remaining_count());
+        assert!(partial.remaining_count() < POSSIBLE_ANSWERS / 4);
+        // Same as trying every symbol in the unknown slot of each guess
+        for idx in 0..POSSIBLE_ANSWERS {
+            let candidate = idx_to_answer(idx);
+            let consistent = partial.history().iter().all(|&(guess, p, s)| {
+                (0..SYMBOL_COUNT as u8).any(|symbol| {
+                    let filled = guess.map(|known| known.unwrap_or(symbol));
+                    compare(&[candidate, filled]) == (p, s)
+                })
+            });
+            assert_eq!(partial.is_consistent(candidate), consistent);
+        }
+    }
+
+    #[test]
+    fn forks_share_until_mutated() {
+        let mut solver = SolverContext::new();
+        solver.apply_result([0, 0, 1, 2], 1, 0);
+        let remaining = solver.remaining_count();
+
+        let mut fork = solver.fork();
+        assert!(Arc::ptr_eq(
+            &solver.answers_known_to_be_false,
+            &fork.answers_known_to_be_false
+        ));
+        fork.apply_result([3, 3, 4, 4], 0, 1);
+        assert!(!Arc::ptr_eq(
+            &solver.answers_known_to_be_false,
+            &fork.answers_known_to_be_false
+        ));
+        assert!(fork.remaining_count() < remaining);
+        assert_eq!(solver.remaining_count(), remaining);
+        assert_eq!(solver.history().len(), 1);
+
+        let hypothetical = solver.with_hypothetical([3, 3, 4, 4], (0, 1), |ctx| {
+            assert_eq!(ctx.history().len(), 2);
+            ctx.remaining_count()
+        });
+        assert_eq!(hypothetical, fork.remaining_count());
+        assert_eq!(solver.remaining_count(), remaining);
+
+        // Resetting a shared context must not touch the other one
+        fork.reset();
+        let other_fork = solver.fork();
+        solver.reset();
+        assert_eq!(solver.remaining_count(), POSSIBLE_ANSWERS);
+        assert_eq!(other_fork.remaining_count(), remaining);
+        assert!(solver.history().is_empty());
+    }
+
     // Testing everything takes too long, only do it every so often. This should do for the most
     // part
     #[test]
     #[ignore = "too slow"]
     fn exhaustive() {
-        exhaustive_test(0, POSSIBLE_ANSWERS);
+        exhaustive_test(0, POSSIBLE_ANSWERS, Strategy::Minimax);
+    }
+    #[test]
+    #[ignore = "too slow"]
+    fn exhaustive_genetic() {
+        exhaustive_test(
+            0,
+            POSSIBLE_ANSWERS,
+            Strategy::Genetic(genetic::GeneticConfig::default()),
+        );
+    }
+    // Run with --nocapture to see how the strategies compare
+    #[test]
+    #[ignore = "too slow"]
+    fn compare_genetic_to_minimax() {
+        const N: usize = POSSIBLE_ANSWERS / 16;
+        let minimax = exhaustive_test(0, N, Strategy::Minimax);
+        let genetic = exhaustive_test(0, N, Strategy::Genetic(Default::default()));
+        println!("minimax (max tries, avg): {:?}", minimax);
+        println!("genetic (max tries, avg): {:?}", genetic);
     }
     const ONE_FIFTH: usize = POSSIBLE_ANSWERS / 5;
     #[test]
     #[ignore = "too slow"]
     fn exhaustive_one_fifths() {
-        exhaustive_test(0, ONE_FIFTH);
+        exhaustive_test(0, ONE_FIFTH, Strategy::Minimax);
     }
     #[test]
     #[ignore = "too slow"]
     fn exhaustive_two_fifths() {
-        exhaustive_test(ONE_FIFTH, ONE_FIFTH * 2);
+        exhaustive_test(ONE_FIFTH, ONE_FIFTH * 2, Strategy::Minimax);
     }
     #[test]
     #[ignore = "too slow"]
     fn exhaustive_three_fifths() {
-        exhaustive_test(ONE_FIFTH * 2, ONE_FIFTH * 3);
+        exhaustive_test(ONE_FIFTH * 2, ONE_FIFTH * 3, Strategy::Minimax);
     }
     #[test]
     #[ignore = "too slow"]
     fn exhaustive_four_fifths() {
-        exhaustive_test(ONE_FIFTH * 3, ONE_FIFTH * 4);
+        exhaustive_test(ONE_FIFTH * 3, ONE_FIFTH * 4, Strategy::Minimax);
     }
     #[test]
     #[ignore = "too slow"]
     fn exhaustive_five_fifths() {
-        exhaustive_test(ONE_FIFTH * 4, POSSIBLE_ANSWERS);
+        exhaustive_test(ONE_FIFTH * 4, POSSIBLE_ANSWERS, Strategy::Minimax);
     }
 
     #[test]
     fn not_quite_exhaustive() {
         const N: usize = POSSIBLE_ANSWERS / 128;
         // First N
-        exhaustive_test(0, N);
+        exhaustive_test(0, N, Strategy::Minimax);
         // Last N
-        exhaustive_test(POSSIBLE_ANSWERS - N, POSSIBLE_ANSWERS);
+        exhaustive_test(POSSIBLE_ANSWERS - N, POSSIBLE_ANSWERS, Strategy::Minimax);
     }
-    // Test all possible answers
-    fn exhaustive_test(first: usize, last: usize) {
+    // Test all possible answers in first..last. Returns the max tries and the average
+    fn exhaustive_test(first: usize, last: usize, strategy: Strategy) -> (usize, f64) {
         use std::cell::RefCell;
         use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 
@@ -425,8 +861,7 @@ mod test {
 
         use rayon::prelude::*;
 
-        const ZERO: AtomicUsize = AtomicUsize::new(0);
-        let guess_count_bins = [ZERO; 16];
+        let guess_count_bins: [AtomicUsize; 32] = Default::default();
         let print_progress = |progress| {
             println!(
                 "Progress {}/{} current max tries:{} current worst guess:{:?}",
@@ -438,16 +873,15 @@ mod test {
         };
         let m = std::sync::Mutex::new(());
         (first..last)
-            .into_iter()
             .collect::<Vec<_>>()
             .into_par_iter()
             .for_each(|i| {
                 let mut state = state
-                    .get_or(|| RefCell::new(SolverContext::new()))
+                    .get_or(|| RefCell::new(SolverContext::with_strategy(strategy)))
                     .try_borrow_mut()
                     .unwrap();
                 let p = progress.fetch_add(1, Ordering::Relaxed);
-                if p % 32 == 0 {
+                if p & 31 == 0 {
                     print_progress(p);
                 }
 
@@ -470,6 +904,7 @@ mod test {
                 .iter()
                 .enumerate()
                 .skip(1)
+                .take(max_tries.load(Ordering::SeqCst))
                 .map(|(idx, x)| format!("{} tries:{}", idx, x.load(Ordering::SeqCst)))
                 .collect::<Vec<_>>()
         );
@@ -480,5 +915,6 @@ mod test {
             .map(|(idx, x)| idx as f64 * x.load(Ordering::SeqCst) as f64 / ((last - first) as f64))
             .sum();
         println!("avg: {}", avg);
+        (max_tries.load(Ordering::SeqCst), avg)
     }
 }
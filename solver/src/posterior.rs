@@ -0,0 +1,216 @@
+//! Ranking the remaining candidates by how likely they are to be the secret, and deciding when to
+//! stop probing and just try the most likely one.
+//!
+//! Every candidate is as likely as its [crate::prior::Prior] weight says (All of them equally
+//! without a prior), so its posterior probability is its weight over the total weight of the
+//! remaining candidates. Strategies pick the guess that splits the candidates best, which is
+//! what we want early on, but near the end winning now is worth more than learning more: see
+//! [SolverContext::goes_for_the_win].
+use crate::{
+    idx_to_answer, tie_break::BestGuess, PossibleAnswer, SolverContext, ANSWER_SIZE, MAX_GUESSES,
+    SYMBOL_COUNT,
+};
+
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+
+/// Going for the win is worth it once the most likely candidate is at least this likely
+pub const GO_FOR_THE_WIN_PROBABILITY: f64 = 0.5;
+
+/// A remaining candidate and the probability of it being the secret
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RankedCandidate {
+    pub secret: PossibleAnswer,
+    pub probability: f64,
+}
+
+impl SolverContext {
+    /// Guesses left before the vault runs out of attempts, counting the next one
+    pub fn attempts_left(&self) -> usize {
+        MAX_GUESSES.saturating_sub(self.history.len())
+    }
+    /// Every remaining candidate with its posterior probability, most likely first. Candidates
+    /// that are equally likely are kept in index order
+    pub fn rank_candidates(&self) -> Vec<RankedCandidate> {
+        let mut ranked = self
+            .candidate_indices()
+            .map(|idx| RankedCandidate {
+                secret: idx_to_answer(idx),
+                probability: self.candidate_weight(idx),
+            })
+            .collect::<Vec<_>>();
+        let total_weight = ranked
+            .iter()
+            .map(|candidate| candidate.probability)
+            .sum::<f64>();
+        if total_weight <= 0.0 {
+            // The prior ruled out every candidate that's left, so it can't tell them apart
+            let uniform = 1.0 / ranked.len() as f64;
+            for candidate in ranked.iter_mut() {
+                candidate.probability = uniform;
+            }
+            return ranked;
+        }
+        for candidate in ranked.iter_mut() {
+            candidate.probability /= total_weight;
+        }
+        // Stable, so ties stay in index order
+        ranked.sort_by(|a, b| {
+            b.probability
+                .partial_cmp(&a.probability)
+                .unwrap_or(Ordering::Equal)
+        });
+        ranked
+    }
+    /// For each slot, the probability of each symbol being in it in the secret (Indexed
+    /// `[slot][symbol]`). All zeros if no secret is consistent with the history
+    pub fn symbol_probabilities(&self) -> [[f64; SYMBOL_COUNT]; ANSWER_SIZE] {
+        let mut probabilities = [[0.0; SYMBOL_COUNT]; ANSWER_SIZE];
+        for candidate in self.rank_candidates() {
+            for (slot, &symbol) in candidate.secret.iter().enumerate() {
+                probabilities[slot][symbol as usize] += candidate.probability;
+            }
+        }
+        probabilities
+    }
+    /// Whether [SolverContext::guess] tries the most likely candidate instead of the guess of the
+    /// strategy. That's the case when:
+    /// - This is the last attempt, so only winning right now matters
+    /// - At most 2 candidates are left, so no guess can split them better than one of them
+    /// - The most likely candidate has at least [GO_FOR_THE_WIN_PROBABILITY] (Only possible with a
+    ///   prior, or with 2 candidates left)
+    pub fn goes_for_the_win(&self) -> bool {
+        let mut candidates = self.candidate_indices();
+        let (first, second, third) = match (candidates.next(), candidates.next(), candidates.next())
+        {
+            (Some(first), second, third) => (first, second, third),
+            (None, ..) => return false,
+        };
+        if self.attempts_left() <= 1 || third.is_none() {
+            return true;
+        }
+        if self.prior.is_none() {
+            return false;
+        }
+        let mut total_weight = self.candidate_weight(first);
+        let mut max_weight = total_weight;
+        for idx in [second, third].into_iter().flatten().chain(candidates) {
+            let weight = self.candidate_weight(idx);
+            total_weight += weight;
+            max_weight = max_weight.max(weight);
+        }
+        // A prior that ruled out every candidate can't make one of them likely
+        total_weight > 0.0 && max_weight >= GO_FOR_THE_WIN_PROBABILITY * total_weight
+    }
+    /// The most likely candidate. Between equally likely candidates the
+    /// [crate::tie_break::TieBreak] decides. None if no secret is consistent with the history
+    pub fn guess_most_likely(&self) -> Option<PossibleAnswer> {
+        let mut best = BestGuess::new(self);
+        for idx in self.candidate_indices() {
+            best.offer(idx_to_answer(idx), -self.candidate_weight(idx));
+        }
+        best.best()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{compare, prior::Prior};
+
+    fn ctx() -> SolverContext {
+        let mut ctx = SolverContext::new();
+        let secret = [1, 4, 6, 9];
+        for guess in [[0, 0, 1, 2], [3, 3, 4, 4], [5, 5, 6, 6]] {
+            let (p, s) = compare(&[secret, guess]);
+            ctx.apply_result(guess, p, s);
+        }
+        ctx
+    }
+
+    #[test]
+    fn uniform_ranking() {
+        let ctx = ctx();
+        let ranked = ctx.rank_candidates();
+        assert_eq!(ranked.len(), ctx.remaining_count());
+        let total = ranked.iter().map(|c| c.probability).sum::<f64>();
+        assert!((total - 1.0).abs() < 1e-9);
+        assert!(ranked
+            .iter()
+            .all(|c| c.probability == ranked[0].probability));
+        assert!(!ctx.goes_for_the_win());
+        assert_eq!(ctx.attempts_left(), MAX_GUESSES - 3);
+    }
+
+    #[test]
+    fn symbol_probabilities() {
+        let ctx = ctx();
+        let probabilities = ctx.symbol_probabilities();
+        for slot in probabilities.iter() {
+            assert!((slot.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+        // 0012 got 0,1, so no symbol of it is in the slot it was guessed in
+        for (slot, &symbol) in [0, 0, 1, 2].iter().enumerate() {
+            assert_eq!(probabilities[slot][symbol], 0.0);
+        }
+
+        let mut solved = SolverContext::new();
+        solved.apply_result([1, 4, 6, 9], 4, 0);
+        assert_eq!(solved.symbol_probabilities()[2][6], 1.0);
+    }
+
+    #[test]
+    fn prior_makes_it_go_for_the_win() {
+        let mut ctx = ctx();
+        let favourite = ctx.rank_candidates()[3].secret;
+        let prior = Prior::from_weights(
+            ctx.rank_candidates()
+                .iter()
+                .map(|c| (c.secret, if c.secret == favourite { 1000.0 } else { 1.0 })),
+        )
+        .unwrap();
+        ctx.set_prior(Some(prior));
+        assert_eq!(ctx.rank_candidates()[0].secret, favourite);
+        assert!(ctx.goes_for_the_win());
+        assert_eq!(ctx.guess(), favourite);
+    }
+
+    #[test]
+    fn prior_that_rules_out_every_candidate() {
+        let mut ctx = ctx();
+        // 0012 got 0,1, so the prior only likes a secret that's already ruled out
+        ctx.set_prior(Some(Prior::from_weights([([0, 0, 1, 2], 1.0)]).unwrap()));
+        let ranked = ctx.rank_candidates();
+        assert_eq!(ranked.len(), ctx.remaining_count());
+        assert!(ranked
+            .iter()
+            .all(|c| c.probability == 1.0 / ranked.len() as f64));
+        for slot in ctx.symbol_probabilities().iter() {
+            assert!((slot.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+        }
+        assert!(!ctx.goes_for_the_win());
+    }
+
+    #[test]
+    fn last_attempt_and_few_candidates() {
+        let mut ctx = ctx();
+        // Repeating a result doesn't rule anything else out, but uses up attempts
+        let (guess, p, s) = ctx.history()[0];
+        while ctx.attempts_left() > 1 {
+            ctx.apply_partial_result(guess, p, s);
+        }
+        assert!(ctx.remaining_count() > 2);
+        assert!(ctx.goes_for_the_win());
+        assert!(ctx.is_consistent(ctx.clone().guess()));
+
+        let mut ctx = SolverContext::new();
+        ctx.apply_result([1, 2, 3, 4], 4, 0);
+        assert!(ctx.goes_for_the_win());
+        assert_eq!(ctx.guess(), [1, 2, 3, 4]);
+
+        ctx.apply_result([1, 2, 3, 4], 0, 0);
+        assert!(!ctx.goes_for_the_win());
+        assert_eq!(ctx.guess_most_likely(), None);
+        assert!(ctx.rank_candidates().is_empty());
+    }
+}
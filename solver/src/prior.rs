@@ -0,0 +1,256 @@
+//! Prior probability distribution over the possible secrets.
+//!
+//! By default every one of the [POSSIBLE_ANSWERS] secrets is assumed to be equally likely. If the
+//! game doesn't actually pick them uniformly, a [Prior] lets strategies like
+//! [crate::Strategy::ExpectedSize] weight each remaining candidate by how likely it is.
+use crate::{answer_to_idx, idx_to_answer, PossibleAnswer, ANSWER_SIZE, POSSIBLE_ANSWERS};
+
+use alloc::{boxed::Box, format, string::String, string::ToString, vec, vec::Vec};
+
+/// Number of ways the 4 slots of a secret can repeat symbols (The Bell number B4). See
+/// [repetition_pattern]
+pub const REPETITION_PATTERNS: usize = 15;
+
+/// Relative weight of every possible secret. Weights don't need to add up to 1
+#[derive(Clone, PartialEq)]
+pub struct Prior {
+    weights: Box<[f64; POSSIBLE_ANSWERS]>,
+}
+
+impl Default for Prior {
+    fn default() -> Self {
+        Self::uniform()
+    }
+}
+
+impl core::fmt::Debug for Prior {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Prior")
+            .field("is_uniform", &self.is_uniform())
+            .finish()
+    }
+}
+
+impl Prior {
+    pub fn uniform() -> Self {
+        Self {
+            weights: vec_to_arr_box!(vec![1.0; POSSIBLE_ANSWERS], POSSIBLE_ANSWERS),
+        }
+    }
+    /// Build a prior from explicit (secret, weight) pairs. Secrets that are not listed get a
+    /// weight of 0
+    pub fn from_weights(
+        weights: impl IntoIterator<Item = (PossibleAnswer, f64)>,
+    ) -> Result<Self, &'static str> {
+        let mut prior = Self {
+            weights: vec_to_arr_box!(vec![0.0; POSSIBLE_ANSWERS], POSSIBLE_ANSWERS),
+        };
+        for (secret, weight) in weights {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err("Prior weights must be finite and not negative");
+            }
+            prior.weights[answer_to_idx(secret)] += weight;
+        }
+        if prior.weights.iter().all(|&w| w == 0.0) {
+            return Err("Prior has no secret with a weight above 0");
+        }
+        Ok(prior)
+    }
+    /// Learn a prior from a log of secrets of already solved vaults.
+    ///
+    /// There are far too many possible secrets to learn each of their probabilities from a few
+    /// hundred solved vaults, so we only learn how likely each [repetition_pattern] is (For
+    /// example, whether the game avoids repeating symbols) and spread that evenly among the
+    /// secrets with that pattern. `smoothing` is a number of pseudo-secrets drawn uniformly,
+    /// spread among the patterns by their size, so patterns that were never seen don't become
+    /// impossible and small ones (Like [4, 4, 4, 4]) don't get an outsized share
+    pub fn learn_from_solved(
+        solved_secrets: impl IntoIterator<Item = PossibleAnswer>,
+        smoothing: f64,
+    ) -> Result<Self, &'static str> {
+        if !smoothing.is_finite() || smoothing < 0.0 {
+            return Err("Smoothing must be finite and not negative");
+        }
+        let mut pattern_sizes = [0usize; REPETITION_PATTERNS];
+        for idx in 0..POSSIBLE_ANSWERS {
+            pattern_sizes[repetition_pattern(idx_to_answer(idx))] += 1;
+        }
+        let mut seen = pattern_sizes.map(|size| smoothing * size as f64 / POSSIBLE_ANSWERS as f64);
+        for secret in solved_secrets {
+            seen[repetition_pattern(secret)] += 1.0;
+        }
+        let mut prior = Self::uniform();
+        for (idx, weight) in prior.weights.iter_mut().enumerate() {
+            let pattern = repetition_pattern(idx_to_answer(idx));
+            *weight = seen[pattern] / pattern_sizes[pattern] as f64;
+        }
+        if prior.weights.iter().all(|&w| w == 0.0) {
+            return Err("Prior has no secret with a weight above 0");
+        }
+        Ok(prior)
+    }
+    /// Parse a prior from text with one `<symbol> <symbol> <symbol> <symbol> <weight>` line per
+    /// secret. Empty lines and lines starting with # are ignored
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut weights = Vec::new();
+        for (line_idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts = line.split_whitespace().collect::<Vec<_>>();
+            let parsed = match parts.as_slice() {
+                [a, b, c, d, weight] => parse_symbols([a, b, c, d])
+                    .and_then(|secret| Some((secret, weight.parse::<f64>().ok()?))),
+                _ => None,
+            };
+            match parsed {
+                Some(x) => weights.push(x),
+                None => return Err(format!("Invalid prior line {}: {}", line_idx + 1, line)),
+            }
+        }
+        Self::from_weights(weights).map_err(|e| e.to_string())
+    }
+    /// Parse a log of solved secrets, one `<symbol> <symbol> <symbol> <symbol>` line per vault.
+    /// Empty lines and lines starting with # are ignored
+    pub fn parse_solved_log(text: &str) -> Result<Vec<PossibleAnswer>, String> {
+        let mut secrets = Vec::new();
+        for (line_idx, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts = line.split_whitespace().collect::<Vec<_>>();
+            let secret = match parts.as_slice() {
+                [a, b, c, d] => parse_symbols([a, b, c, d]),
+                _ => None,
+            };
+            match secret {
+                Some(secret) => secrets.push(secret),
+                None => return Err(format!("Invalid secret on line {}: {}", line_idx + 1, line)),
+            }
+        }
+        Ok(secrets)
+    }
+    pub fn weight(&self, secret: PossibleAnswer) -> f64 {
+        self.weights[answer_to_idx(secret)]
+    }
+    pub(crate) fn weight_of_idx(&self, idx: usize) -> f64 {
+        self.weights[idx]
+    }
+    pub fn is_uniform(&self) -> bool {
+        self.weights.iter().all(|&w| w == self.weights[0])
+    }
+}
+
+fn parse_symbols(symbols: [&&str; ANSWER_SIZE]) -> Option<PossibleAnswer> {
+    let mut secret = [0; ANSWER_SIZE];
+    for (out, symbol) in secret.iter_mut().zip(symbols) {
+        *out = symbol.parse::<u8>().ok()?;
+        if *out as usize >= crate::SYMBOL_COUNT {
+            return None;
+        }
+    }
+    Some(secret)
+}
+
+/// Which slots of the secret repeat the same symbol, as an index in 0..[REPETITION_PATTERNS].
+/// For example [1, 2, 3, 4] and [5, 6, 7, 8] share a pattern (No repeats), and so do
+/// [1, 1, 2, 3] and [7, 7, 0, 4]
+pub fn repetition_pattern(secret: PossibleAnswer) -> usize {
+    // Relabel symbols in order of first appearance, so [7, 7, 0, 4] becomes [0, 0, 1, 2]
+    let mut relabeled = [0u8; ANSWER_SIZE];
+    let mut next_label = 0;
+    for i in 0..ANSWER_SIZE {
+        relabeled[i] = match (0..i).find(|&j| secret[j] == secret[i]) {
+            Some(j) => relabeled[j],
+            None => {
+                next_label += 1;
+                next_label - 1
+            }
+        };
+    }
+    // The first slot is always 0, so each relabeled sequence is one of these
+    const PATTERNS: [[u8; ANSWER_SIZE]; REPETITION_PATTERNS] = [
+        [0, 0, 0, 0],
+        [0, 0, 0, 1],
+        [0, 0, 1, 0],
+        [0, 0, 1, 1],
+        [0, 0, 1, 2],
+        [0, 1, 0, 0],
+        [0, 1, 0, 1],
+        [0, 1, 0, 2],
+        [0, 1, 1, 0],
+        [0, 1, 1, 1],
+        [0, 1, 1, 2],
+        [0, 1, 2, 0],
+        [0, 1, 2, 1],
+        [0, 1, 2, 2],
+        [0, 1, 2, 3],
+    ];
+    PATTERNS.iter().position(|&p| p == relabeled).unwrap()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn patterns() {
+        assert_eq!(
+            repetition_pattern([1, 2, 3, 4]),
+            repetition_pattern([5, 6, 7, 8])
+        );
+        assert_eq!(
+            repetition_pattern([1, 1, 2, 3]),
+            repetition_pattern([7, 7, 0, 4])
+        );
+        assert_ne!(
+            repetition_pattern([1, 1, 2, 3]),
+            repetition_pattern([1, 2, 3, 4])
+        );
+    }
+
+    #[test]
+    fn learns_fewer_duplicates() {
+        let log = "# secrets from a few vaults\n1 2 3 4\n5 6 7 8\n0 3 9 11\n\n2 2 3 4\n";
+        let secrets = Prior::parse_solved_log(log).unwrap();
+        assert_eq!(secrets.len(), 4);
+        let prior = Prior::learn_from_solved(secrets.clone(), 1.0).unwrap();
+        assert!(!prior.is_uniform());
+        // 3 of the 4 logged secrets have no repeats, so most of the probability should go there
+        let mut pattern_weights = [0.0; REPETITION_PATTERNS];
+        for idx in 0..POSSIBLE_ANSWERS {
+            pattern_weights[repetition_pattern(idx_to_answer(idx))] += prior.weight_of_idx(idx);
+        }
+        let no_repeats = pattern_weights[repetition_pattern([4, 5, 6, 7])];
+        let total = pattern_weights.iter().sum::<f64>();
+        // 3/4 of the log, against 11880/20736 of the uniform prior
+        let uniform_share = (12 * 11 * 10 * 9) as f64 / POSSIBLE_ANSWERS as f64;
+        assert!(no_repeats / total > uniform_share);
+        assert!(no_repeats > pattern_weights[repetition_pattern([4, 4, 6, 7])]);
+        assert!(prior.weight([4, 4, 4, 4]) > 0.0);
+        // Per secret, an unseen pattern is no likelier than under the uniform prior
+        assert!(prior.weight([4, 4, 4, 4]) / total < 1.0 / POSSIBLE_ANSWERS as f64);
+
+        // Without data, smoothing alone gives back the uniform prior
+        let prior = Prior::learn_from_solved([], 1.0).unwrap();
+        let first = prior.weight([0, 0, 0, 0]);
+        assert!((0..POSSIBLE_ANSWERS)
+            .all(|idx| (prior.weight_of_idx(idx) - first).abs() < 1e-12 * first));
+
+        assert!(Prior::learn_from_solved(secrets.clone(), -1.0).is_err());
+        assert!(Prior::learn_from_solved(secrets, f64::NAN).is_err());
+        assert!(Prior::learn_from_solved([], 0.0).is_err());
+    }
+
+    #[test]
+    fn parses() {
+        let prior = Prior::parse("1 2 3 4 2.5\n1 2 3 5 0.5").unwrap();
+        assert_eq!(prior.weight([1, 2, 3, 4]), 2.5);
+        assert_eq!(prior.weight([1, 2, 3, 6]), 0.0);
+        assert!(Prior::parse("1 2 3 12 1").is_err());
+        assert!(Prior::parse("1 2 3 4 -1").is_err());
+        assert!(Prior::parse("1 2 3").is_err());
+    }
+}
@@ -0,0 +1,319 @@
+//! Serialisable snapshot of a vault in progress, to save it and resume it somewhere else.
+//!
+//! A [SolverState] has everything needed to rebuild a [SolverContext]: the strategy configuration
+//! and the results of the guesses made so far (Which are the constraints that rule out secrets).
+//! It can be written as a small human editable text file (See [SolverState::to_text]), and with
+//! the `serde` feature it also implements `Serialize` and `Deserialize` for any other format.
+//!
+//! The text format looks like this, with one guess per line written as its 4 symbols in base 12
+//! (`a` and `b` for symbols 10 and 11, `?` for a symbol that couldn't be read), followed by the
+//! correct positions and correct symbols:
+//!
+//! ```text
+//! nickracker-state 3
+//! strategy minimax
+//! tie-break lowest-index
+//! 0012:1,0
+//! 3344:0,1
+//! 5?66:0,0
+//! ```
+use crate::{
+    tie_break::TieBreak, PartialAnswer, PossibleAnswer, SolverContext, Strategy, ANSWER_SIZE,
+    SYMBOL_COUNT,
+};
+
+use alloc::{format, string::String, string::ToString, vec::Vec};
+
+/// Version written by [SolverState::to_text] and [SolverContext::state]. Bump it whenever the
+/// meaning of a stored state changes
+pub const STATE_VERSION: u32 = 3;
+
+const TEXT_HEADER: &str = "nickracker-state";
+
+/// A guess and the result the vault gave for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GuessResult {
+    /// Unknown symbols are None. Versions before 3 didn't have them
+    pub guess: PartialAnswer,
+    pub correct_positions: u8,
+    pub correct_symbols: u8,
+}
+
+impl GuessResult {
+    pub fn new(guess: PossibleAnswer, correct_positions: u8, correct_symbols: u8) -> Self {
+        Self::new_partial(guess.map(Some), correct_positions, correct_symbols)
+    }
+    pub fn new_partial(guess: PartialAnswer, correct_positions: u8, correct_symbols: u8) -> Self {
+        Self {
+            guess,
+            correct_positions,
+            correct_symbols,
+        }
+    }
+    /// Parse the `0012:1,0` form used in the text format
+    pub fn parse(text: &str) -> Option<Self> {
+        let (guess, result) = text.trim().split_once(':')?;
+        let (correct_positions, correct_symbols) = result.split_once(',')?;
+        Some(Self::new_partial(
+            parse_partial_answer(guess)?,
+            correct_positions.trim().parse().ok()?,
+            correct_symbols.trim().parse().ok()?,
+        ))
+    }
+}
+
+impl core::fmt::Display for GuessResult {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{}:{},{}",
+            format_partial_answer(self.guess),
+            self.correct_positions,
+            self.correct_symbols
+        )
+    }
+}
+
+/// A symbol as a base 12 digit, `0` to `9` then `a` and `b`
+pub fn symbol_char(symbol: u8) -> char {
+    core::char::from_digit(symbol as u32, SYMBOL_COUNT as u32).unwrap_or('?')
+}
+
+/// Write a code as 4 base 12 digits, like `0a1b`
+pub fn format_answer(answer: PossibleAnswer) -> String {
+    answer.iter().map(|&symbol| symbol_char(symbol)).collect()
+}
+
+/// Parse a code written as 4 base 12 digits, like `0a1b`
+pub fn parse_answer(text: &str) -> Option<PossibleAnswer> {
+    let answer = parse_partial_answer(text)?;
+    if answer.iter().any(Option::is_none) {
+        return None;
+    }
+    Some(answer.map(Option::unwrap))
+}
+
+/// Write a code with unknown symbols, with `?` for them, like `0?1b`
+pub fn format_partial_answer(answer: PartialAnswer) -> String {
+    answer
+        .iter()
+        .map(|symbol| symbol.map_or('?', symbol_char))
+        .collect()
+}
+
+/// Parse a code written as 4 base 12 digits or `?` for unknown symbols, like `0?1b`
+pub fn parse_partial_answer(text: &str) -> Option<PartialAnswer> {
+    let mut answer = [None; ANSWER_SIZE];
+    let mut chars = text.trim().chars();
+    for symbol in answer.iter_mut() {
+        *symbol = match chars.next()? {
+            '?' => None,
+            c => Some(c.to_digit(SYMBOL_COUNT as u32)? as u8),
+        };
+    }
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(answer)
+}
+
+/// Everything needed to rebuild a [SolverContext]. Doesn't include the [crate::prior::Prior],
+/// which is usually learned or loaded separately and can be big
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolverState {
+    /// [STATE_VERSION] when the state was written
+    pub version: u32,
+    pub strategy: Strategy,
+    /// Added in version 2, older states used the default
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tie_break: TieBreak,
+    /// The guesses made so far and their results, oldest first
+    pub history: Vec<GuessResult>,
+}
+
+impl SolverState {
+    pub fn new(strategy: Strategy, history: impl IntoIterator<Item = GuessResult>) -> Self {
+        Self {
+            version: STATE_VERSION,
+            strategy,
+            tie_break: TieBreak::default(),
+            history: history.into_iter().collect(),
+        }
+    }
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "{} {}\nstrategy {}\ntie-break {}\n",
+            TEXT_HEADER,
+            self.version,
+            self.strategy.id(),
+            self.tie_break.id()
+        );
+        for entry in self.history.iter() {
+            out.push_str(&entry.to_string());
+            out.push('\n');
+        }
+        out
+    }
+    /// Parse the format written by [SolverState::to_text]. Empty lines and lines starting with #
+    /// are ignored. Missing strategy or tie break lines mean the defaults
+    pub fn parse(text: &str) -> Result<Self, String> {
+        Self::parse_with_lines(text).map(|(state, _)| state)
+    }
+    /// Same as [SolverState::parse], also returning the line number (Starting at 1) of every
+    /// history entry, to point at the line when an entry turns out to be wrong
+    pub fn parse_with_lines(text: &str) -> Result<(Self, Vec<usize>), String> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .map(|(line_idx, line)| (line_idx + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'));
+
+        let version = match lines.next() {
+            Some((_, line)) => line
+                .strip_prefix(TEXT_HEADER)
+                .and_then(|version| version.trim().parse::<u32>().ok())
+                .ok_or_else(|| {
+                    format!("Not a solver state, it should start with {}", TEXT_HEADER)
+                })?,
+            None => return Err("Empty solver state".to_string()),
+        };
+        if version > STATE_VERSION {
+            return Err(format!("Unsupported solver state version {}", version));
+        }
+
+        let mut state = Self {
+            version,
+            ..Self::new(Strategy::default(), [])
+        };
+        let mut entry_lines = Vec::new();
+        for (line_number, line) in lines {
+            if let Some(id) = line.strip_prefix("strategy ") {
+                state.strategy = Strategy::from_id(id.trim())
+                    .ok_or_else(|| format!("Unknown strategy on line {}: {}", line_number, line))?;
+            } else if let Some(id) = line.strip_prefix("tie-break ") {
+                state.tie_break = TieBreak::from_id(id.trim()).ok_or_else(|| {
+                    format!("Unknown tie break on line {}: {}", line_number, line)
+                })?;
+            } else {
+                let entry = GuessResult::parse(line)
+                    .ok_or_else(|| format!("Invalid guess on line {}: {}", line_number, line))?;
+                state.history.push(entry);
+                entry_lines.push(line_number);
+            }
+        }
+        Ok((state, entry_lines))
+    }
+}
+
+impl SolverContext {
+    /// Snapshot of the strategy, tie break and history, see [SolverState]
+    pub fn state(&self) -> SolverState {
+        let mut state = SolverState::new(
+            self.strategy,
+            self.history
+                .iter()
+                .map(|&(guess, correct_positions, correct_symbols)| {
+                    GuessResult::new_partial(guess, correct_positions, correct_symbols)
+                }),
+        );
+        state.tie_break = self.tie_break;
+        state
+    }
+    /// Rebuild a context from a [SolverState], applying its history in order. Fails if the state
+    /// is from a newer version, has invalid values, or no secret is consistent with it
+    pub fn from_state(state: &SolverState) -> Result<Self, &'static str> {
+        if state.version > STATE_VERSION {
+            return Err("Unsupported solver state version");
+        }
+        let mut ctx = Self::with_strategy(state.strategy);
+        ctx.set_tie_break(state.tie_break);
+        for entry in state.history.iter() {
+            if entry
+                .guess
+                .iter()
+                .flatten()
+                .any(|&symbol| symbol as usize >= SYMBOL_COUNT)
+            {
+                return Err("Invalid symbol in solver state");
+            }
+            if entry.correct_positions as usize + entry.correct_symbols as usize > ANSWER_SIZE {
+                return Err("Invalid result in solver state");
+            }
+            ctx.apply_partial_result(entry.guess, entry.correct_positions, entry.correct_symbols);
+        }
+        if ctx.remaining_count() == 0 {
+            return Err("No secret is consistent with the solver state");
+        }
+        Ok(ctx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn text_round_trip() {
+        let mut ctx = SolverContext::with_strategy(Strategy::Genetic(Default::default()));
+        ctx.set_tie_break(TieBreak::Seeded(7));
+        ctx.apply_result([0, 0, 1, 2], 1, 0);
+        ctx.apply_result([3, 3, 4, 4], 0, 1);
+        ctx.apply_result([10, 11, 6, 6], 0, 2);
+        ctx.apply_partial_result([Some(7), None, Some(8), Some(8)], 0, 0);
+
+        let text = ctx.state().to_text();
+        assert!(text.starts_with("nickracker-state 3\nstrategy genetic-"));
+        assert!(text.contains("\ntie-break seeded-7\n"));
+        assert!(text.contains("\nab66:0,2\n"));
+        assert!(text.contains("\n7?88:0,0\n"));
+        let state = SolverState::parse(&text).unwrap();
+        assert_eq!(state, ctx.state());
+        // Header, strategy and tie break come first
+        let with_comment = format!("# saved\n{}", text);
+        let (_, lines) = SolverState::parse_with_lines(&with_comment).unwrap();
+        assert_eq!(lines, [5, 6, 7, 8]);
+
+        let resumed = SolverContext::from_state(&state).unwrap();
+        assert_eq!(resumed.remaining_count(), ctx.remaining_count());
+        assert_eq!(resumed.clone().guess(), ctx.clone().guess());
+    }
+
+    #[test]
+    fn rejects_bad_states() {
+        assert!(SolverState::parse("").is_err());
+        assert!(SolverState::parse("nickracker-state 4\n").is_err());
+        assert!(SolverState::parse("nickracker-state 1\n0012:1\n").is_err());
+        assert!(SolverState::parse("nickracker-state 1\nstrategy magic\n").is_err());
+        // Version 1 states and states without a strategy line use the defaults
+        let state = SolverState::parse("nickracker-state 1\n# comment\n\n0012:4,0\n").unwrap();
+        assert_eq!(state.version, 1);
+        assert_eq!(state.strategy, Strategy::Minimax);
+        assert_eq!(state.tie_break, TieBreak::LowestIndex);
+        assert!(state.to_text().starts_with("nickracker-state 1\n"));
+        assert!(SolverContext::from_state(&state).is_ok());
+
+        let inconsistent = SolverState::new(
+            Strategy::Minimax,
+            [
+                GuessResult::new([0, 0, 1, 2], 4, 0),
+                GuessResult::new([0, 0, 1, 2], 0, 0),
+            ],
+        );
+        assert!(SolverContext::from_state(&inconsistent).is_err());
+        let invalid = SolverState::new(Strategy::Minimax, [GuessResult::new([0, 0, 1, 2], 3, 2)]);
+        assert!(SolverContext::from_state(&invalid).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let state = SolverState::new(
+            Strategy::ExpectedSize,
+            [GuessResult::new([0, 0, 1, 2], 1, 0)],
+        );
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<SolverState>(&json).unwrap(), state);
+    }
+}
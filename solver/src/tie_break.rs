@@ -0,0 +1,200 @@
+//! How strategies pick between guesses with the same score.
+//!
+//! Strategies score guesses and keep the best one, and there are usually many guesses tied for
+//! the best score. Keeping the first one found biases guesses towards low symbol indices, so the
+//! [TieBreak] policy of the [SolverContext] decides instead. Every policy is deterministic, so the
+//! same history, strategy and policy always give the same guess.
+use crate::ANSWER_SIZE;
+use crate::{answer_to_idx, genetic::SplitMix64, state, PossibleAnswer, SolverContext};
+
+use alloc::{format, string::String, string::ToString};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TieBreak {
+    /// Keep the guess with the lowest index (See [crate::answer_to_idx]). The default
+    LowestIndex,
+    /// Prefer guesses that could be the secret, so a tie can still win the vault. Then lowest
+    /// index. Only with this policy do minimax and expected size also score codes that can't be
+    /// the secret, so they have something to break ties with (Slower, but sometimes splits the
+    /// candidates better). The genetic strategy only ever scores consistent codes
+    PreferConsistent,
+    /// Prefer guesses that keep more of these symbols in their slots (Like the symbols the player
+    /// already selected in the game). Then lowest index
+    PreferSelected([Option<u8>; ANSWER_SIZE]),
+    /// Pick between tied guesses pseudo-randomly, always the same way for the same seed
+    Seeded(u64),
+}
+
+impl Default for TieBreak {
+    fn default() -> Self {
+        TieBreak::LowestIndex
+    }
+}
+
+impl TieBreak {
+    /// Identifies the policy in text formats (See [crate::state]). Never contains whitespace
+    pub fn id(&self) -> String {
+        match self {
+            TieBreak::LowestIndex => "lowest-index".to_string(),
+            TieBreak::PreferConsistent => "prefer-consistent".to_string(),
+            TieBreak::PreferSelected(selected) => {
+                format!(
+                    "prefer-selected-{}",
+                    state::format_partial_answer(*selected)
+                )
+            }
+            TieBreak::Seeded(seed) => format!("seeded-{}", seed),
+        }
+    }
+    /// Inverse of [TieBreak::id]
+    pub fn from_id(id: &str) -> Option<Self> {
+        match id {
+            "lowest-index" => Some(TieBreak::LowestIndex),
+            "prefer-consistent" => Some(TieBreak::PreferConsistent),
+            _ => {
+                if let Some(seed) = id.strip_prefix("seeded-") {
+                    return Some(TieBreak::Seeded(seed.parse().ok()?));
+                }
+                let selected = id.strip_prefix("prefer-selected-")?;
+                // Ids never have whitespace
+                if selected.trim() != selected {
+                    return None;
+                }
+                Some(TieBreak::PreferSelected(state::parse_partial_answer(
+                    selected,
+                )?))
+            }
+        }
+    }
+    /// Among guesses with the same score, the one with the lowest key wins
+    fn key(&self, ctx: &SolverContext, guess: PossibleAnswer) -> (u32, u64) {
+        let idx = answer_to_idx(guess) as u64;
+        match self {
+            TieBreak::LowestIndex => (0, idx),
+            TieBreak::PreferConsistent => (!ctx.is_consistent(guess) as u32, idx),
+            TieBreak::PreferSelected(selected) => {
+                let kept = selected
+                    .iter()
+                    .zip(guess.iter())
+                    .filter(|(selected, &symbol)| **selected == Some(symbol))
+                    .count();
+                ((ANSWER_SIZE - kept) as u32, idx)
+            }
+            TieBreak::Seeded(seed) => (0, SplitMix64::new(seed ^ idx).next_u64()),
+        }
+    }
+}
+
+/// Keeps the guess with the lowest score offered so far, using the [TieBreak] of the context
+/// between guesses with the same score
+pub(crate) struct BestGuess<'a, S> {
+    ctx: &'a SolverContext,
+    best: Option<(S, (u32, u64), PossibleAnswer)>,
+}
+
+impl<'a, S: PartialOrd> BestGuess<'a, S> {
+    pub(crate) fn new(ctx: &'a SolverContext) -> Self {
+        Self { ctx, best: None }
+    }
+    pub(crate) fn offer(&mut self, guess: PossibleAnswer, score: S) {
+        let tie_break = self.ctx.tie_break();
+        match &self.best {
+            Some((best_score, best_key, _)) => {
+                if score < *best_score {
+                    self.best = Some((score, tie_break.key(self.ctx, guess), guess));
+                } else if score == *best_score {
+                    // Only worth computing the key when there's a tie
+                    let key = tie_break.key(self.ctx, guess);
+                    if key < *best_key {
+                        self.best = Some((score, key, guess));
+                    }
+                }
+            }
+            None => self.best = Some((score, tie_break.key(self.ctx, guess), guess)),
+        }
+    }
+    pub(crate) fn best(self) -> Option<PossibleAnswer> {
+        self.best.map(|(_, _, guess)| guess)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Strategy;
+
+    fn ctx_with(tie_break: TieBreak) -> SolverContext {
+        let mut ctx = SolverContext::new();
+        ctx.set_tie_break(tie_break);
+        ctx.apply_result([0, 0, 1, 2], 0, 1);
+        ctx.apply_result([3, 3, 4, 4], 0, 1);
+        ctx.apply_result([5, 5, 6, 6], 1, 0);
+        ctx
+    }
+
+    #[test]
+    fn ids_round_trip() {
+        for tie_break in [
+            TieBreak::LowestIndex,
+            TieBreak::PreferConsistent,
+            TieBreak::PreferSelected([Some(11), None, Some(0), None]),
+            TieBreak::Seeded(42),
+        ] {
+            assert_eq!(TieBreak::from_id(&tie_break.id()), Some(tie_break));
+        }
+        assert_eq!(TieBreak::from_id("prefer-selected-0c??"), None);
+    }
+
+    #[test]
+    fn policies_pick_between_ties() {
+        let lowest = ctx_with(TieBreak::LowestIndex).guess();
+        assert_eq!(SolverContext::new().tie_break(), TieBreak::LowestIndex);
+
+        // Ties between a code that can't be the secret and one that can
+        let ctx = ctx_with(TieBreak::PreferConsistent);
+        let (inconsistent, consistent) = ([0, 0, 0, 0], ctx.clone().guess());
+        assert!(!ctx.is_consistent(inconsistent) && ctx.is_consistent(consistent));
+        let mut best = BestGuess::new(&ctx);
+        best.offer(inconsistent, 1);
+        best.offer(consistent, 1);
+        assert_eq!(best.best(), Some(consistent));
+        let lowest_index = ctx_with(TieBreak::LowestIndex);
+        let mut best = BestGuess::new(&lowest_index);
+        best.offer(consistent, 1);
+        best.offer(inconsistent, 1);
+        assert_eq!(best.best(), Some(inconsistent));
+
+        // Expected size scores every code with this policy, and here the best split comes from
+        // a code that can't be the secret
+        let mut expected_size = ctx_with(TieBreak::PreferConsistent);
+        expected_size.set_strategy(Strategy::ExpectedSize);
+        let guess = expected_size.clone().guess();
+        assert!(!expected_size.is_consistent(guess));
+        assert_eq!(expected_size.guess(), guess);
+
+        let selected = [Some(7), Some(8), None, None];
+        let preferred = ctx_with(TieBreak::PreferSelected(selected)).guess();
+        let kept = |guess: PossibleAnswer| {
+            selected
+                .iter()
+                .zip(guess.iter())
+                .filter(|(selected, &symbol)| **selected == Some(symbol))
+                .count()
+        };
+        assert!(kept(preferred) >= kept(lowest));
+
+        // Seeded picks are reproducible, and don't always agree with the lowest index
+        let seeded = (0..8)
+            .map(|seed| ctx_with(TieBreak::Seeded(seed)).guess())
+            .collect::<alloc::vec::Vec<_>>();
+        for (seed, &guess) in seeded.iter().enumerate() {
+            assert_eq!(ctx_with(TieBreak::Seeded(seed as u64)).guess(), guess);
+        }
+        assert!(seeded.iter().any(|&guess| guess != lowest));
+
+        let mut expected_size = ctx_with(TieBreak::Seeded(3));
+        expected_size.set_strategy(Strategy::ExpectedSize);
+        assert_eq!(expected_size.clone().guess(), expected_size.guess());
+    }
+}
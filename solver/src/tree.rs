@@ -0,0 +1,155 @@
+//! Export of the decision tree a strategy follows.
+//!
+//! Starting from some state (Usually the start of a vault), we make the guess the strategy would
+//! make, and then for every result that guess could get we apply it and continue from there. The
+//! resulting tree can be written as Graphviz DOT for visualisation or as nested JSON for tooling.
+use crate::{compare, idx_to_answer, PossibleAnswer, SolverContext, ANSWER_SIZE};
+
+use alloc::{string::String, vec::Vec};
+use core::fmt::Write;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TreeNode {
+    /// The guess the strategy makes here
+    pub guess: PossibleAnswer,
+    /// Number of possible secrets left before making the guess
+    pub candidates: usize,
+    /// One child for every (correct_positions, correct_symbols) result of the guess that at least
+    /// one candidate would give, except for the one that means the guess was right. Empty if the
+    /// tree was not expanded this far
+    pub children: Vec<((u8, u8), TreeNode)>,
+}
+
+impl TreeNode {
+    /// Expand the tree of the strategy in `ctx` from its current state, `depth` guesses deep (A
+    /// depth of 1 only has the next guess)
+    pub fn build(ctx: &SolverContext, depth: usize) -> Self {
+        Self::build_inner(&mut ctx.fork(), depth)
+    }
+    fn build_inner(ctx: &mut SolverContext, depth: usize) -> Self {
+        let guess = ctx.guess();
+        let candidates = ctx.remaining_count();
+        let mut children = Vec::new();
+        if depth > 1 {
+            let mut results = [[false; ANSWER_SIZE + 1]; ANSWER_SIZE + 1];
+            for idx in ctx.candidate_indices() {
+                let (p, s) = compare(&[idx_to_answer(idx), guess]);
+                results[p as usize][s as usize] = true;
+            }
+            results[ANSWER_SIZE][0] = false;
+
+            for (p, row) in results.iter().enumerate() {
+                for (s, _) in row.iter().enumerate().filter(|(_, &possible)| possible) {
+                    let result = (p as u8, s as u8);
+                    let child = ctx.with_hypothetical(guess, result, |child_ctx| {
+                        Self::build_inner(child_ctx, depth - 1)
+                    });
+                    children.push((result, child));
+                }
+            }
+        }
+        Self {
+            guess,
+            candidates,
+            children,
+        }
+    }
+    /// Total number of nodes in the tree
+    pub fn node_count(&self) -> usize {
+        1 + self
+            .children
+            .iter()
+            .map(|(_, child)| child.node_count())
+            .sum::<usize>()
+    }
+    /// Graphviz DOT of the tree. Each node is labeled with its guess and candidate count, and
+    /// each edge with the result that leads to it
+    pub fn to_dot(&self) -> String {
+        fn write_node(out: &mut String, node: &TreeNode, next_id: &mut usize) -> usize {
+            let id = *next_id;
+            *next_id += 1;
+            let _ = writeln!(
+                out,
+                "    n{} [label=\"{:?}\\n{} candidates\"];",
+                id, node.guess, node.candidates
+            );
+            for ((p, s), child) in node.children.iter() {
+                let child_id = write_node(out, child, next_id);
+                let _ = writeln!(out, "    n{} -> n{} [label=\"{},{}\"];", id, child_id, p, s);
+            }
+            id
+        }
+        let mut out = String::from("digraph strategy {\n    node [shape=box];\n");
+        write_node(&mut out, self, &mut 0);
+        out.push_str("}\n");
+        out
+    }
+    /// The tree as nested JSON objects, like
+    /// `{"guess":[0,0,1,2],"candidates":20736,"children":[{"result":[0,0],"node":{...}}]}`
+    pub fn to_json(&self) -> String {
+        fn write_node(out: &mut String, node: &TreeNode) {
+            let _ = write!(
+                out,
+                "{{\"guess\":[{},{},{},{}],\"candidates\":{},\"children\":[",
+                node.guess[0], node.guess[1], node.guess[2], node.guess[3], node.candidates
+            );
+            for (i, ((p, s), child)) in node.children.iter().enumerate() {
+                if i != 0 {
+                    out.push(',');
+                }
+                let _ = write!(out, "{{\"result\":[{},{}],\"node\":", p, s);
+                write_node(out, child);
+                out.push('}');
+            }
+            out.push_str("]}");
+        }
+        let mut out = String::new();
+        write_node(&mut out, self);
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tree_covers_every_secret() {
+        let tree = TreeNode::build(&SolverContext::new(), 2);
+        assert_eq!(tree.guess, [0, 0, 1, 2]);
+        assert_eq!(tree.candidates, crate::POSSIBLE_ANSWERS);
+        // Every secret other than the guess itself ends up in exactly one branch
+        let in_children = tree
+            .children
+            .iter()
+            .map(|(_, child)| child.candidates)
+            .sum::<usize>();
+        assert_eq!(in_children, crate::POSSIBLE_ANSWERS - 1);
+        // Unless so few candidates are left that it goes for the win
+        assert!(tree
+            .children
+            .iter()
+            .all(|(_, child)| child.guess == [3, 3, 4, 4] || child.candidates <= 2));
+        assert_eq!(tree.node_count(), 1 + tree.children.len());
+    }
+
+    #[test]
+    fn exports() {
+        let mut ctx = SolverContext::new();
+        ctx.apply_result([0, 0, 1, 2], 1, 1);
+        ctx.apply_result([3, 3, 4, 4], 0, 1);
+        ctx.apply_result([5, 5, 6, 6], 0, 1);
+        let tree = TreeNode::build(&ctx, 2);
+
+        let dot = tree.to_dot();
+        assert!(dot.starts_with("digraph strategy {"));
+        assert_eq!(dot.matches(" -> ").count(), tree.node_count() - 1);
+
+        let json = tree.to_json();
+        assert!(json.starts_with(&format!(
+            "{{\"guess\":[{},{},{},{}],\"candidates\":{},",
+            tree.guess[0], tree.guess[1], tree.guess[2], tree.guess[3], tree.candidates
+        )));
+        assert_eq!(json.matches("\"guess\"").count(), tree.node_count());
+    }
+}
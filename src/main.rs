@@ -64,7 +64,7 @@ mod vault_analyzer;
 
 use overlay::*;
 
-pub const MAX_GUESSES: usize = 12;
+pub const MAX_GUESSES: usize = solver::MAX_GUESSES;
 
 const SYMBOL_BMPS: [&[u8]; SYMBOL_COUNT] = [
     include_bytes!("bmps/symbol0.bmp"),
@@ -114,6 +114,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         || {
             let mut vault_analyzer = vault_analyzer::VaultAnalyzerCtx::new().unwrap();
             let mut last_vault = None;
+            // The history the misreads were last looked for, the vault also changes with the
+            // selected symbols
+            let mut repaired_history = None;
             let mut s_t = std::time::Instant::now();
             capture::for_each(move |screenshot, width, height| {
                 let t = std::time::Instant::now();
@@ -135,11 +138,40 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                     solver.reset();
 
                     println!("analyzed_main: {:?}", &analyzed);
-                    for (ans, correct_positions, correct_symbols) in analyzed.guesses_iter() {
-                        solver.apply_result(ans, correct_positions as u8, correct_symbols as u8);
+                    // Printed in the saved state format, to resume the vault in the console
+                    let state = analyzed.solver_state(solver.strategy());
+                    println!("state_main:\n{}", state.to_text());
+                    for entry in state.history.iter() {
+                        solver.apply_partial_result(
+                            entry.guess,
+                            entry.correct_positions,
+                            entry.correct_symbols,
+                        );
                     }
-                    let guess = solver.guess();
+                    if solver.remaining_count() == 0
+                        && repaired_history.as_deref() != Some(solver.history())
+                    {
+                        repaired_history = Some(solver.history().to_vec());
+                        // Something was misread, show what it most likely was
+                        for repair in solver::consistency::find_repairs(solver.history(), 3) {
+                            println!("possible_misread_main: {:?}", &repair);
+                        }
+                    }
+                    let mut guess = solver.guess();
                     println!("guess_main: {:?}", &guess);
+                    // If the player already started selecting a different guess, suggest the best
+                    // way to finish it instead of making them start over
+                    let selected = analyzed.selected_symbols();
+                    let differs = selected
+                        .iter()
+                        .zip(guess.iter())
+                        .any(|(selected, symbol)| matches!(selected, Some(s) if s != symbol));
+                    if differs {
+                        if let Some(completion) = solver.complete_guess(selected) {
+                            println!("completion_main: {:?}", &completion);
+                            guess = completion.guess;
+                        }
+                    }
                     let mut msg = [None; 4];
                     for (i, &guess) in guess.iter().enumerate() {
                         msg[i] = Some((guess, 50 + 100 * i, 150));
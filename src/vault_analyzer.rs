@@ -9,21 +9,37 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 pub struct AnalyzedMinotaurVault {
     /// The symbols currently being selected for the next guess
     selected_symbols: [Option<u8>; ANSWER_SIZE],
-    /// The guesses made in the past: The 4 symbols for each and the 2 resulting numbers
-    made_guesses: [Option<([u8; ANSWER_SIZE], u8, u8)>; MAX_GUESSES - 1],
+    /// The guesses made in the past: The 4 symbols for each (None for the ones that couldn't be
+    /// recognized) and the 2 resulting numbers
+    made_guesses: [Option<([Option<u8>; ANSWER_SIZE], u8, u8)>; MAX_GUESSES - 1],
 }
 impl AnalyzedMinotaurVault {
+    /// The symbols selected for the next guess, None for the slots that weren't picked yet
+    pub fn selected_symbols(&self) -> [Option<u8>; ANSWER_SIZE] {
+        self.selected_symbols
+    }
     pub fn selected_iter(&self) -> impl Iterator<Item = usize> + '_ {
         let mut it = self.selected_symbols.iter().cloned();
         std::iter::from_fn(move || it.next().flatten().map(|x| x as usize))
     }
-    pub fn guesses_iter(&self) -> impl Iterator<Item = ([u8; ANSWER_SIZE], usize, usize)> + '_ {
-        let mut it = self.made_guesses.iter().cloned();
-        std::iter::from_fn(move || {
-            it.next()
-                .flatten()
-                .map(|x| (x.0, x.1 as usize, x.2 as usize))
-        })
+    /// The guesses made so far. Rows whose result couldn't be read are skipped, not the end
+    pub fn guesses_iter(
+        &self,
+    ) -> impl Iterator<Item = ([Option<u8>; ANSWER_SIZE], usize, usize)> + '_ {
+        self.made_guesses
+            .iter()
+            .flatten()
+            .map(|x| (x.0, x.1 as usize, x.2 as usize))
+    }
+    /// The guesses made so far as a [solver::state::SolverState], to save or resume the vault in
+    /// another tool
+    pub fn solver_state(&self, strategy: solver::Strategy) -> solver::state::SolverState {
+        solver::state::SolverState::new(
+            strategy,
+            self.guesses_iter().map(|(guess, p, s)| {
+                solver::state::GuessResult::new_partial(guess, p as u8, s as u8)
+            }),
+        )
     }
 }
 
@@ -297,6 +313,30 @@ fn to_grayscale(b: &[u8]) -> Vec<u8> {
         .collect::<Vec<u8>>()
 }
 
+// Width of the 4 symbols of a past guess, to the left of its result
+const GUESS_SYMBOLS_W: usize = 130;
+
+/// Place the symbols found in a past guess fragment as (x, symbol, distance) in their slots, when
+/// some are missing and the order alone doesn't tell us their slots. The slot is estimated from
+/// the x position, and if two symbols land in the same slot the best match wins
+fn symbols_by_slot(positions: &mut [(usize, u8, f32)]) -> [Option<u8>; ANSWER_SIZE] {
+    // NaN distances (Which shouldn't happen) go last instead of breaking the sort
+    positions.sort_unstable_by(|(_x, _n, d), (_x2, _n2, d2)| {
+        d.is_nan()
+            .cmp(&d2.is_nan())
+            .then(d.partial_cmp(d2).unwrap_or(std::cmp::Ordering::Equal))
+    });
+    let slot_w = GUESS_SYMBOLS_W / ANSWER_SIZE;
+    let mut symbols = [None; ANSWER_SIZE];
+    for (x, symbol_n, _) in positions.iter() {
+        let slot = (*x / slot_w).min(ANSWER_SIZE - 1);
+        if symbols[slot].is_none() {
+            symbols[slot] = Some(*symbol_n);
+        }
+    }
+    symbols
+}
+
 #[cfg_attr(feature = "trace", tracing::instrument(skip(ctx, ss)))]
 fn find_minotaur_vault_impl(
     ctx: &mut VaultAnalyzerCtx,
@@ -576,7 +616,7 @@ fn find_minotaur_vault_impl(
         let (guess_result1, guess_result2) = guess_result?;
         // We extract a fragment rectangle to the left of the comma containing the 4 symbols
         let (xx, yy, w, h) = (
-            x + window_x - 130,
+            x + window_x - GUESS_SYMBOLS_W,
             y + window_y + SELECTOR_AREA_HEIGHT - 10,
             160,
             45,
@@ -627,13 +667,14 @@ fn find_minotaur_vault_impl(
             }
         }
 
+        // Not fatal, the solver can work with the symbols we did find. Even with none the result
+        // still counts as an attempt
         if guess_symbols_x_positions.len() < ANSWER_SIZE {
             println!(
                 "============= WARN: Missing symbols in guess {}/{}",
                 guess_symbols_x_positions.len(),
                 ANSWER_SIZE
             );
-            return None;
         }
 
         // If we have too many guesses keep the best 4
@@ -667,15 +708,21 @@ fn find_minotaur_vault_impl(
                 i += 1;
             }
 
-            guess_symbols_x_positions.drain(ANSWER_SIZE..);
+            if guess_symbols_x_positions.len() > ANSWER_SIZE {
+                guess_symbols_x_positions.drain(ANSWER_SIZE..);
+            }
         }
 
-        // Sort by x position to get leftmost first and store the guesses using that order
-        guess_symbols_x_positions.sort_unstable_by(|(x, _n, _d), (x2, _n2, _d2)| x.cmp(x2));
+        let mut guess_symbols = [None; ANSWER_SIZE];
+        if guess_symbols_x_positions.len() == ANSWER_SIZE {
+            // Sort by x position to get leftmost first and store the guesses using that order
+            guess_symbols_x_positions.sort_unstable_by(|(x, _n, _d), (x2, _n2, _d2)| x.cmp(x2));
 
-        let mut guess_symbols = [0u8; ANSWER_SIZE];
-        for (idx, (_, symbol_n, _)) in guess_symbols_x_positions.iter().enumerate() {
-            guess_symbols[idx] = *symbol_n;
+            for (idx, (_, symbol_n, _)) in guess_symbols_x_positions.iter().enumerate() {
+                guess_symbols[idx] = Some(*symbol_n);
+            }
+        } else {
+            guess_symbols = symbols_by_slot(&mut guess_symbols_x_positions);
         }
 
         *past_guess_result = Some((guess_symbols, guess_result1, guess_result2));
@@ -813,6 +860,13 @@ mod test {
             f(bmp::parse_rgb_bmp(include_bytes!($name)).unwrap())
         }};
     }
+    /// Guesses where every symbol was recognized
+    fn known<const N: usize>(
+        guesses: [Option<([u8; ANSWER_SIZE], u8, u8)>; N],
+    ) -> [Option<([Option<u8>; ANSWER_SIZE], u8, u8)>; N] {
+        guesses.map(|guess| guess.map(|(symbols, p, s)| (symbols.map(Some), p, s)))
+    }
+
     macro_rules! gen_test {
         ($test_name:ident, $file_name: expr, $selected: expr, $guess: expr, $x:expr, $y:expr) => {
             #[test]
@@ -821,7 +875,7 @@ mod test {
                     Some((
                         AnalyzedMinotaurVault {
                             selected_symbols: $selected,
-                            made_guesses: $guess
+                            made_guesses: known($guess)
                         },
                         $x,
                         $y
@@ -845,6 +899,70 @@ mod test {
         None, None, None, None, None, None, None, None, None],
         1110, 214);
 
+    #[test]
+    fn missing_symbols() {
+        let slot_w = GUESS_SYMBOLS_W / ANSWER_SIZE;
+        // Symbols 3 and 9 both look like the third one, 9 is the better match
+        let mut positions = [
+            (2 * slot_w + 5, 3, 0.1),
+            (2, 5, 0.05),
+            (2 * slot_w + 3, 9, 0.01),
+        ];
+        assert_eq!(
+            symbols_by_slot(&mut positions),
+            [Some(5), None, Some(9), None]
+        );
+        // Past the last slot, and a NaN distance doesn't stop the others
+        let mut positions = [(GUESS_SYMBOLS_W + 10, 7, f32::NAN), (slot_w, 1, 0.02)];
+        assert_eq!(
+            symbols_by_slot(&mut positions),
+            [None, Some(1), None, Some(7)]
+        );
+        // A row where no symbol was found still has its result
+        assert_eq!(symbols_by_slot(&mut []), [None; ANSWER_SIZE]);
+    }
+
+    #[test]
+    fn rows_after_a_gap() {
+        let mut made_guesses = [None; MAX_GUESSES - 1];
+        made_guesses[0] = Some(([Some(0), Some(0), Some(1), Some(2)], 1, 0));
+        // Unreadable result
+        made_guesses[1] = None;
+        // No symbols found
+        made_guesses[2] = Some(([None; ANSWER_SIZE], 0, 1));
+        made_guesses[3] = Some(([Some(5), Some(5), Some(6), Some(6)], 0, 0));
+        let vault = AnalyzedMinotaurVault {
+            selected_symbols: [None; ANSWER_SIZE],
+            made_guesses,
+        };
+        let guesses = vault.guesses_iter().collect::<Vec<_>>();
+        assert_eq!(guesses.len(), 3);
+        assert_eq!(guesses[2], ([Some(5), Some(5), Some(6), Some(6)], 0, 0));
+
+        let ctx = solver::SolverContext::from_state(&vault.solver_state(solver::Strategy::Minimax))
+            .unwrap();
+        assert_eq!(ctx.attempts_left(), MAX_GUESSES - 3);
+    }
+
+    #[test]
+    fn state_round_trip() {
+        let (vault, _, _) = analyze!("ss/test1p.bmp").unwrap();
+        let state = vault.solver_state(solver::Strategy::Minimax);
+        assert_eq!(state.history.len(), MAX_GUESSES - 1);
+        assert_eq!(
+            state.history[0],
+            solver::state::GuessResult::new([0, 6, 7, 1], 1, 0)
+        );
+        let parsed = solver::state::SolverState::parse(&state.to_text()).unwrap();
+        assert_eq!(parsed, state);
+        let ctx = solver::SolverContext::from_state(&parsed).unwrap();
+        let history = vault
+            .guesses_iter()
+            .map(|(guess, p, s)| (guess, p as u8, s as u8))
+            .collect::<Vec<_>>();
+        assert_eq!(ctx.history(), history);
+    }
+
     #[test]
     fn tt1() {
         let (w, w_w, w_h) = bmp::parse_rgb_bmp(include_bytes!("ss/windowed_1.bmp")).unwrap();
@@ -887,7 +1005,7 @@ mod test {
                 AnalyzedMinotaurVault {
                     selected_symbols: [Some(4), Some(10), Some(11), Some(5)],
                     #[rustfmt::skip]
-                    made_guesses: [Some(([0, 0, 0, 0], 0, 0)), Some(([1, 1, 1, 1], 0, 0)), None, None, None, None, None, None, None, None, None]
+                    made_guesses: known([Some(([0, 0, 0, 0], 0, 0)), Some(([1, 1, 1, 1], 0, 0)), None, None, None, None, None, None, None, None, None])
                 },
                 1110,
                 214
@@ -904,7 +1022,7 @@ mod test {
                     Some((
                         AnalyzedMinotaurVault {
                             selected_symbols: [Some($sym_n); ANSWER_SIZE],
-                            made_guesses: [Some(([$sym_n; 4], $g1, $g2)); MAX_GUESSES - 1]
+                            made_guesses: known([Some(([$sym_n; 4], $g1, $g2)); MAX_GUESSES - 1])
                         },
                         $x,
                         $y